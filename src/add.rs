@@ -0,0 +1,318 @@
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
+
+use crate::memory_pool::collect_vec;
+use crate::pointer_traits::{Cpu, CpuMut, TensorPointer};
+use crate::shape::broadcast_shapes;
+use crate::tensor::{CpuTensor, TensorBase};
+
+fn zip_map<P1, P2, E, F>(a: &TensorBase<P1, E>, b: &TensorBase<P2, E>, f: F) -> CpuTensor<E>
+where
+    P1: TensorPointer<Elem = E> + Cpu,
+    P2: TensorPointer<Elem = E> + Cpu,
+    E: Copy,
+    F: Fn(E, E) -> E,
+{
+    let shape = broadcast_shapes(&[a.shape.clone(), b.shape.clone()])
+        .unwrap_or_else(|err| panic!("{}", err));
+    let a_iter = a.broadcast_iter(&shape).unwrap();
+    let b_iter = b.broadcast_iter(&shape).unwrap();
+    let v = collect_vec(a_iter.zip(b_iter).map(|(x, y)| f(*x, *y)));
+    TensorBase::from_vec(v, shape)
+}
+
+fn map<P, E, F>(a: &TensorBase<P, E>, f: F) -> CpuTensor<E>
+where
+    P: TensorPointer<Elem = E> + Cpu,
+    E: Copy,
+    F: Fn(E) -> E,
+{
+    let v = collect_vec(a.iter().map(|x| f(*x)));
+    TensorBase::from_vec(v, a.shape.clone())
+}
+
+fn zip_assign<P1, P2, E, F>(a: &mut TensorBase<P1, E>, b: &TensorBase<P2, E>, f: F)
+where
+    P1: TensorPointer<Elem = E> + CpuMut,
+    P2: TensorPointer<Elem = E> + Cpu,
+    E: Copy,
+    F: Fn(E, E) -> E,
+{
+    let b_iter = b
+        .broadcast_iter(&a.shape)
+        .unwrap_or_else(|err| panic!("{}", err));
+    for (x, y) in a.iter_mut().zip(b_iter) {
+        *x = f(*x, *y);
+    }
+}
+
+fn map_assign<P, E, F>(a: &mut TensorBase<P, E>, f: F)
+where
+    P: TensorPointer<Elem = E> + CpuMut,
+    E: Copy,
+    F: Fn(E) -> E,
+{
+    for x in a.iter_mut() {
+        *x = f(*x);
+    }
+}
+
+macro_rules! impl_binary_op {
+    ($trait:ident, $fn_name:ident, $op:tt) => {
+        impl<'a, 'b, P1, P2, E> $trait<&'b TensorBase<P2, E>> for &'a TensorBase<P1, E>
+        where
+            P1: TensorPointer<Elem = E> + Cpu,
+            P2: TensorPointer<Elem = E> + Cpu,
+            E: Copy + $trait<Output = E>,
+        {
+            type Output = CpuTensor<E>;
+            fn $fn_name(self, rhs: &'b TensorBase<P2, E>) -> Self::Output {
+                zip_map(self, rhs, |a, b| a $op b)
+            }
+        }
+
+        impl<'a, P1, P2, E> $trait<TensorBase<P2, E>> for &'a TensorBase<P1, E>
+        where
+            P1: TensorPointer<Elem = E> + Cpu,
+            P2: TensorPointer<Elem = E> + Cpu,
+            E: Copy + $trait<Output = E>,
+        {
+            type Output = CpuTensor<E>;
+            fn $fn_name(self, rhs: TensorBase<P2, E>) -> Self::Output {
+                zip_map(self, &rhs, |a, b| a $op b)
+            }
+        }
+
+        impl<'b, P1, P2, E> $trait<&'b TensorBase<P2, E>> for TensorBase<P1, E>
+        where
+            P1: TensorPointer<Elem = E> + Cpu,
+            P2: TensorPointer<Elem = E> + Cpu,
+            E: Copy + $trait<Output = E>,
+        {
+            type Output = CpuTensor<E>;
+            fn $fn_name(self, rhs: &'b TensorBase<P2, E>) -> Self::Output {
+                zip_map(&self, rhs, |a, b| a $op b)
+            }
+        }
+
+        impl<P1, P2, E> $trait<TensorBase<P2, E>> for TensorBase<P1, E>
+        where
+            P1: TensorPointer<Elem = E> + Cpu,
+            P2: TensorPointer<Elem = E> + Cpu,
+            E: Copy + $trait<Output = E>,
+        {
+            type Output = CpuTensor<E>;
+            fn $fn_name(self, rhs: TensorBase<P2, E>) -> Self::Output {
+                zip_map(&self, &rhs, |a, b| a $op b)
+            }
+        }
+
+        impl<'a, P, E> $trait<E> for &'a TensorBase<P, E>
+        where
+            P: TensorPointer<Elem = E> + Cpu,
+            E: Copy + $trait<Output = E>,
+        {
+            type Output = CpuTensor<E>;
+            fn $fn_name(self, rhs: E) -> Self::Output {
+                map(self, |a| a $op rhs)
+            }
+        }
+
+        impl<P, E> $trait<E> for TensorBase<P, E>
+        where
+            P: TensorPointer<Elem = E> + Cpu,
+            E: Copy + $trait<Output = E>,
+        {
+            type Output = CpuTensor<E>;
+            fn $fn_name(self, rhs: E) -> Self::Output {
+                map(&self, |a| a $op rhs)
+            }
+        }
+    };
+}
+
+impl_binary_op!(Add, add, +);
+impl_binary_op!(Sub, sub, -);
+impl_binary_op!(Mul, mul, *);
+impl_binary_op!(Div, div, /);
+
+macro_rules! impl_scalar_lhs_op {
+    ($trait:ident, $fn_name:ident, $op:tt, $($ty:ty),*) => {
+        $(
+            impl<'b, P> $trait<&'b TensorBase<P, $ty>> for $ty
+            where
+                P: TensorPointer<Elem = $ty> + Cpu,
+            {
+                type Output = CpuTensor<$ty>;
+                fn $fn_name(self, rhs: &'b TensorBase<P, $ty>) -> Self::Output {
+                    map(rhs, |b| self $op b)
+                }
+            }
+
+            impl<P> $trait<TensorBase<P, $ty>> for $ty
+            where
+                P: TensorPointer<Elem = $ty> + Cpu,
+            {
+                type Output = CpuTensor<$ty>;
+                fn $fn_name(self, rhs: TensorBase<P, $ty>) -> Self::Output {
+                    map(&rhs, |b| self $op b)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_scalar_lhs_ops {
+    ($($ty:ty),*) => {
+        impl_scalar_lhs_op!(Add, add, +, $($ty),*);
+        impl_scalar_lhs_op!(Sub, sub, -, $($ty),*);
+        impl_scalar_lhs_op!(Mul, mul, *, $($ty),*);
+        impl_scalar_lhs_op!(Div, div, /, $($ty),*);
+    };
+}
+
+impl_scalar_lhs_ops!(f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! impl_assign_op {
+    ($trait:ident, $fn_name:ident, $op:tt) => {
+        impl<'b, P1, P2, E> $trait<&'b TensorBase<P2, E>> for TensorBase<P1, E>
+        where
+            P1: TensorPointer<Elem = E> + CpuMut,
+            P2: TensorPointer<Elem = E> + Cpu,
+            E: Copy + $trait,
+        {
+            fn $fn_name(&mut self, rhs: &'b TensorBase<P2, E>) {
+                zip_assign(self, rhs, |mut a, b| {
+                    a $op b;
+                    a
+                })
+            }
+        }
+
+        impl<P1, P2, E> $trait<TensorBase<P2, E>> for TensorBase<P1, E>
+        where
+            P1: TensorPointer<Elem = E> + CpuMut,
+            P2: TensorPointer<Elem = E> + Cpu,
+            E: Copy + $trait,
+        {
+            fn $fn_name(&mut self, rhs: TensorBase<P2, E>) {
+                zip_assign(self, &rhs, |mut a, b| {
+                    a $op b;
+                    a
+                })
+            }
+        }
+
+        impl<P, E> $trait<E> for TensorBase<P, E>
+        where
+            P: TensorPointer<Elem = E> + CpuMut,
+            E: Copy + $trait,
+        {
+            fn $fn_name(&mut self, rhs: E) {
+                map_assign(self, |mut a| {
+                    a $op rhs;
+                    a
+                })
+            }
+        }
+    };
+}
+
+impl_assign_op!(AddAssign, add_assign, +=);
+impl_assign_op!(SubAssign, sub_assign, -=);
+impl_assign_op!(MulAssign, mul_assign, *=);
+impl_assign_op!(DivAssign, div_assign, /=);
+
+#[cfg(test)]
+use crate::shape::Shape;
+
+#[test]
+fn add_same_shape() {
+    let a = CpuTensor::from_vec(vec![1, 2, 3, 4], Shape::new(vec![2, 2]));
+    let b = CpuTensor::from_vec(vec![10, 20, 30, 40], Shape::new(vec![2, 2]));
+    let c = &a + &b;
+    assert_eq!(c.shape(), Shape::new(vec![2, 2]));
+    assert_eq!(c.to_vec(), vec![11, 22, 33, 44]);
+}
+
+#[test]
+fn sub_broadcast_row() {
+    let a = CpuTensor::from_vec(vec![1., 2., 3., 4., 5., 6.], Shape::new(vec![2, 3]));
+    let b = CpuTensor::from_vec(vec![1., 1., 2.], Shape::new(vec![3]));
+    let c = a - b;
+    assert_eq!(c.shape(), Shape::new(vec![2, 3]));
+    assert_eq!(c.to_vec(), vec![0., 1., 1., 3., 4., 4.]);
+}
+
+#[test]
+fn mul_broadcast_both() {
+    let a = CpuTensor::from_vec(vec![1, 2, 3], Shape::new(vec![3, 1]));
+    let b = CpuTensor::from_vec(vec![1, 10], Shape::new(vec![1, 2]));
+    let c = &a * &b;
+    assert_eq!(c.shape(), Shape::new(vec![3, 2]));
+    assert_eq!(c.to_vec(), vec![1, 10, 2, 20, 3, 30]);
+}
+
+#[test]
+#[should_panic]
+fn add_broadcast_panic() {
+    let a = CpuTensor::from_vec(vec![1, 2, 3], Shape::new(vec![3]));
+    let b = CpuTensor::from_vec(vec![1, 2], Shape::new(vec![2]));
+    let _ = &a + &b;
+}
+
+#[test]
+fn div_strided_view() {
+    use crate::index;
+    let a = CpuTensor::from_vec((0..25).collect(), Shape::new(vec![5, 5]));
+    let b = CpuTensor::from_vec(vec![2; 3], Shape::new(vec![3]));
+    let c = a.slice(index![2..4, ..;2]) / &b;
+    assert_eq!(c.to_vec(), vec![5, 6, 7, 7, 8, 9]);
+}
+
+#[test]
+fn add_swapped_axis() {
+    let mut a = CpuTensor::from_vec(vec![0, 1, 2, 3, 4, 5], Shape::new(vec![2, 3]));
+    a.swap_axis(0, 1);
+    let b = CpuTensor::from_vec(vec![0; 6], Shape::new(vec![3, 2]));
+    let c = &a + &b;
+    assert_eq!(c.to_vec(), vec![0, 3, 1, 4, 2, 5]);
+}
+
+#[test]
+fn scalar_ops() {
+    let a = CpuTensor::from_vec(vec![1f64, 2., 4.], Shape::new(vec![3]));
+    assert_eq!((&a + 1.).to_vec(), vec![2., 3., 5.]);
+    assert_eq!((&a * 2.).to_vec(), vec![2., 4., 8.]);
+    assert_eq!((8f64 / &a).to_vec(), vec![8., 4., 2.]);
+    assert_eq!((1f64 - a).to_vec(), vec![0., -1., -3.]);
+}
+
+#[test]
+fn add_assign_broadcast() {
+    let mut a = CpuTensor::from_vec(vec![1, 2, 3, 4, 5, 6], Shape::new(vec![2, 3]));
+    let b = CpuTensor::from_vec(vec![10, 20, 30], Shape::new(vec![3]));
+    a += &b;
+    assert_eq!(a.to_vec(), vec![11, 22, 33, 14, 25, 36]);
+    a -= 1;
+    assert_eq!(a.to_vec(), vec![10, 21, 32, 13, 24, 35]);
+}
+
+#[test]
+fn mul_assign_view_mut() {
+    use crate::index;
+    let mut a = CpuTensor::from_vec(vec![1, 2, 3, 4, 5, 6], Shape::new(vec![2, 3]));
+    let b = CpuTensor::from_vec(vec![2, 3], Shape::new(vec![2]));
+    {
+        let mut v = a.slice_mut(index![1, ..;2]);
+        v *= &b;
+    }
+    assert_eq!(a.to_vec(), vec![1, 2, 3, 8, 5, 18]);
+}
+
+#[test]
+#[should_panic]
+fn add_assign_broadcast_panic() {
+    let mut a = CpuTensor::from_vec(vec![1, 2, 3], Shape::new(vec![3]));
+    let b = CpuTensor::from_vec(vec![1, 2, 3, 4, 5, 6], Shape::new(vec![2, 3]));
+    a += b;
+}
@@ -1,9 +1,12 @@
 use std::convert::TryInto;
 
-use crate::tensor::{CpuViewMutTensor, CpuViewTensor};
+use crate::cast::Cast;
+use crate::pointer_cpu::OwnedCpu;
+use crate::pointer_traits::{Cpu, Owned, TensorPointer};
+use crate::tensor::{CpuTensor, CpuViewMutTensor, CpuViewTensor, TensorBase};
 use crate::wrapper::cpu_blas::*;
 
-use super::{CpuLayout, CpuTranspose};
+use super::{CpuLayout, CpuPart, CpuSide, CpuTranspose};
 
 // use num_traits::Num;
 
@@ -39,7 +42,7 @@ pub fn axpy_uncheckd<E: CpuAxpy>(
     incx: i32,
     incy: i32,
     a: CpuViewTensor<E>,
-    b: CpuViewMutTensor<E>,
+    mut b: CpuViewMutTensor<E>,
 ) {
     let a_slice = a.to_slice();
     let b_slice = b.to_slice_mut();
@@ -77,7 +80,7 @@ pub fn copy_unchecked<E: CpuCopy>(
     incx: i32,
     incy: i32,
     x: CpuViewTensor<E>,
-    y: CpuViewMutTensor<E>,
+    mut y: CpuViewMutTensor<E>,
 ) {
     let x_slice = x.to_slice();
     let y_slice = y.to_slice_mut();
@@ -164,6 +167,66 @@ pub fn sdot(x: CpuViewTensor<f32>, y: CpuViewTensor<f32>) -> Option<f64> {
     Some(sdot_unchecked(x, y, incx, incy))
 }
 
+/// 複素数ベクトル同士の内積を、共役を取らずに計算します。
+/// sum(x_i * y_i)
+/// 実数のベクトルを与えた場合は`dot`と同じ結果になります。
+pub fn dotu_unchecked<E: CpuDotu<Out = E>>(
+    x: CpuViewTensor<E>,
+    y: CpuViewTensor<E>,
+    incx: i32,
+    incy: i32,
+) -> E {
+    E::cpu_dotu(
+        x.num_elms().try_into().unwrap(),
+        x.to_slice(),
+        incx,
+        y.to_slice(),
+        incy,
+    )
+}
+
+/// 複素数ベクトル同士の内積を、共役を取らずに計算します。
+/// sum(x_i * y_i)
+/// 実数のベクトルを与えた場合は`dot`と同じ結果になります。
+pub fn dotu<E: CpuDotu<Out = E>>(x: CpuViewTensor<E>, y: CpuViewTensor<E>) -> Option<E> {
+    if x.shape().num_dim() != 1 || x.shape() != y.shape() {
+        return None;
+    }
+    let incx = x.stride[0].try_into().unwrap();
+    let incy = y.stride[0].try_into().unwrap();
+    Some(dotu_unchecked(x, y, incx, incy))
+}
+
+/// 複素数ベクトル同士の内積を、xの共役を取って計算します。
+/// sum(conj(x_i) * y_i)
+/// 実数のベクトルを与えた場合は`dot`と同じ結果になります。
+pub fn dotc_unchecked<E: CpuDotc<Out = E>>(
+    x: CpuViewTensor<E>,
+    y: CpuViewTensor<E>,
+    incx: i32,
+    incy: i32,
+) -> E {
+    E::cpu_dotc(
+        x.num_elms().try_into().unwrap(),
+        x.to_slice(),
+        incx,
+        y.to_slice(),
+        incy,
+    )
+}
+
+/// 複素数ベクトル同士の内積を、xの共役を取って計算します。
+/// sum(conj(x_i) * y_i)
+/// 実数のベクトルを与えた場合は`dot`と同じ結果になります。
+pub fn dotc<E: CpuDotc<Out = E>>(x: CpuViewTensor<E>, y: CpuViewTensor<E>) -> Option<E> {
+    if x.shape().num_dim() != 1 || x.shape() != y.shape() {
+        return None;
+    }
+    let incx = x.stride[0].try_into().unwrap();
+    let incy = y.stride[0].try_into().unwrap();
+    Some(dotc_unchecked(x, y, incx, incy))
+}
+
 /// ベクトルのユークリッドノルム、つまり普通のノルムを計算します。
 /// 結果は戻り値として返ってきます。
 /// 複素数のベクトルを与えた場合でも、実数が返ってくることに注意してください。
@@ -198,8 +261,8 @@ pub fn nrm2<E: CpuNrm2>(x: CpuViewTensor<E>) -> Option<<E as CpuNrm2>::Out> {
 /// X(i) := c * X(i) + s * Y(i)
 /// Y(i) :=-s * X(i) + c * Y(i)
 pub fn rot_unchecked<E: CpuRot>(
-    x: CpuViewMutTensor<E>,
-    y: CpuViewMutTensor<E>,
+    mut x: CpuViewMutTensor<E>,
+    mut y: CpuViewMutTensor<E>,
     incx: i32,
     incy: i32,
     c: E,
@@ -218,7 +281,7 @@ pub fn rot_unchecked<E: CpuRot>(
 
 /// 与えたベクトルをスカラ倍します。
 /// 複素数のベクトルの場合は、実数倍をする専用のルーチンが用意されています
-pub fn scal_unchecked<E: CpuScal>(alpha: E, x: CpuViewMutTensor<E>, incx: i32) {
+pub fn scal_unchecked<E: CpuScal>(alpha: E, mut x: CpuViewMutTensor<E>, incx: i32) {
     E::cpu_scal(
         x.num_elms().try_into().unwrap(),
         alpha,
@@ -280,7 +343,7 @@ pub fn gbmv_unchecked<E: CpuGbmv>(
     ku: i32,
     a: CpuViewTensor<E>,
     x: CpuViewTensor<E>,
-    y: CpuViewMutTensor<E>,
+    mut y: CpuViewMutTensor<E>,
     incx: i32,
     incy: i32,
 ) {
@@ -318,7 +381,7 @@ pub fn gemv_unchecked<E: CpuGemv>(
     beta: E,
     a: CpuViewTensor<E>,
     x: CpuViewTensor<E>,
-    y: CpuViewMutTensor<E>,
+    mut y: CpuViewMutTensor<E>,
     incx: i32,
     incy: i32,
 ) {
@@ -386,7 +449,7 @@ pub fn ger_unchecked<E: CpuGer>(
     alpha: E,
     x: CpuViewTensor<E>,
     y: CpuViewTensor<E>,
-    a: CpuViewMutTensor<E>,
+    mut a: CpuViewMutTensor<E>,
     incx: i32,
     incy: i32,
 ) {
@@ -434,6 +497,171 @@ pub fn ger<E: CpuGer>(
     }
 }
 
+/// 行列のメモリの並びと、leading dimensionを返す。
+/// どちらの軸のstrideも1でない場合はBLASに渡せないので`None`を返す。
+fn matrix_layout<P, E>(a: &TensorBase<P, E>) -> Option<(CpuLayout, i32)>
+where
+    P: TensorPointer<Elem = E>,
+{
+    if a.shape.num_dim() != 2 {
+        return None;
+    }
+    let (rows, cols) = (a.shape[0], a.shape[1]);
+    let (st0, st1) = (a.stride[0], a.stride[1]);
+    if st1 == 1 && st0 >= cols.max(1) {
+        Some((CpuLayout::RowMajor, st0.try_into().unwrap()))
+    } else if st0 == 1 && st1 >= rows.max(1) {
+        Some((CpuLayout::ColumnMajor, st1.try_into().unwrap()))
+    } else {
+        None
+    }
+}
+
+/// 転置や共役転置を適用した後の行列の(行数, 列数)
+fn op_shape<P, E>(trans: &CpuTranspose, a: &TensorBase<P, E>) -> (isize, isize)
+where
+    P: TensorPointer<Elem = E>,
+{
+    match trans {
+        CpuTranspose::None => (a.shape[0], a.shape[1]),
+        _ => (a.shape[1], a.shape[0]),
+    }
+}
+
+/// 連続した行列のleading dimension
+fn contiguous_ld(layout: CpuLayout, rows: i32, cols: i32) -> i32 {
+    match layout {
+        CpuLayout::RowMajor => cols,
+        CpuLayout::ColumnMajor => rows,
+    }
+}
+
+///  A := alpha * x y^H + A
+///
+/// `ger`の複素数版で、yの共役を取ります。実数では`ger`と同じです。
+/// Aは連続した行列として扱います。
+pub fn gerc_unchecked<E: CpuGerc>(
+    layout: CpuLayout,
+    alpha: E,
+    x: CpuViewTensor<E>,
+    y: CpuViewTensor<E>,
+    mut a: CpuViewMutTensor<E>,
+    incx: i32,
+    incy: i32,
+) {
+    let m = x.shape_vec()[0].try_into().unwrap();
+    let n = y.shape_vec()[0].try_into().unwrap();
+    E::cpu_gerc(
+        layout.into(),
+        m,
+        n,
+        alpha,
+        x.to_slice(),
+        incx,
+        y.to_slice(),
+        incy,
+        a.to_slice_mut(),
+        contiguous_ld(layout, m, n),
+    );
+}
+
+///  A := alpha * x y^H + A
+///
+/// `ger`の複素数版で、yの共役を取ります。実数では`ger`と同じです。
+/// Aのメモリの並びはstrideから判断します。
+pub fn gerc<E: CpuGerc>(
+    alpha: E,
+    x: CpuViewTensor<E>,
+    y: CpuViewTensor<E>,
+    mut a: CpuViewMutTensor<E>,
+) -> Option<()> {
+    if x.shape.num_dim() != 1
+        || y.shape.num_dim() != 1
+        || a.shape.num_dim() != 2
+        || a.shape[0] != x.shape[0]
+        || a.shape[1] != y.shape[0]
+    {
+        return None;
+    }
+    let (layout, lda) = matrix_layout(&a)?;
+    E::cpu_gerc(
+        layout.into(),
+        x.shape[0].try_into().unwrap(),
+        y.shape[0].try_into().unwrap(),
+        alpha,
+        x.to_slice(),
+        x.stride[0].try_into().unwrap(),
+        y.to_slice(),
+        y.stride[0].try_into().unwrap(),
+        a.to_slice_mut(),
+        lda,
+    );
+    Some(())
+}
+
+///  A := alpha * x y^T + A
+///
+/// `ger`の複素数版で、共役を取りません。実数では`ger`と同じです。
+/// Aは連続した行列として扱います。
+pub fn geru_unchecked<E: CpuGeru>(
+    layout: CpuLayout,
+    alpha: E,
+    x: CpuViewTensor<E>,
+    y: CpuViewTensor<E>,
+    mut a: CpuViewMutTensor<E>,
+    incx: i32,
+    incy: i32,
+) {
+    let m = x.shape_vec()[0].try_into().unwrap();
+    let n = y.shape_vec()[0].try_into().unwrap();
+    E::cpu_geru(
+        layout.into(),
+        m,
+        n,
+        alpha,
+        x.to_slice(),
+        incx,
+        y.to_slice(),
+        incy,
+        a.to_slice_mut(),
+        contiguous_ld(layout, m, n),
+    );
+}
+
+///  A := alpha * x y^T + A
+///
+/// `ger`の複素数版で、共役を取りません。実数では`ger`と同じです。
+/// Aのメモリの並びはstrideから判断します。
+pub fn geru<E: CpuGeru>(
+    alpha: E,
+    x: CpuViewTensor<E>,
+    y: CpuViewTensor<E>,
+    mut a: CpuViewMutTensor<E>,
+) -> Option<()> {
+    if x.shape.num_dim() != 1
+        || y.shape.num_dim() != 1
+        || a.shape.num_dim() != 2
+        || a.shape[0] != x.shape[0]
+        || a.shape[1] != y.shape[0]
+    {
+        return None;
+    }
+    let (layout, lda) = matrix_layout(&a)?;
+    E::cpu_geru(
+        layout.into(),
+        x.shape[0].try_into().unwrap(),
+        y.shape[0].try_into().unwrap(),
+        alpha,
+        x.to_slice(),
+        x.stride[0].try_into().unwrap(),
+        y.to_slice(),
+        y.stride[0].try_into().unwrap(),
+        a.to_slice_mut(),
+        lda,
+    );
+    Some(())
+}
+
 /// 一般行列と一般行列の積を計算します。
 /// 結果を別途渡した行列にスカラ倍したものを加算します（詳しくは計算式参照）
 #[allow(clippy::too_many_arguments)]
@@ -445,26 +673,33 @@ pub fn gemm_unchecked<E: CpuGemm>(
     beta: E,
     a: CpuViewTensor<E>,
     b: CpuViewTensor<E>,
-    c: CpuViewMutTensor<E>,
+    mut c: CpuViewMutTensor<E>,
 ) {
-    let m = a.shape_vec()[0].try_into().unwrap();
-    let n = b.shape_vec()[1].try_into().unwrap();
-    let k = a.shape_vec()[1].try_into().unwrap();
+    let (m, k) = op_shape(&transa, &a);
+    let n = c.shape_vec()[1].try_into().unwrap();
+    let (a0, a1) = (
+        a.shape[0].try_into().unwrap(),
+        a.shape[1].try_into().unwrap(),
+    );
+    let (b0, b1) = (
+        b.shape[0].try_into().unwrap(),
+        b.shape[1].try_into().unwrap(),
+    );
     E::cpu_gemm(
         layout.into(),
         transa.into(),
         transb.into(),
-        m,
+        m.try_into().unwrap(),
         n,
-        k,
+        k.try_into().unwrap(),
         alpha,
         a.to_slice(),
-        m,
+        contiguous_ld(layout, a0, a1),
         b.to_slice(),
-        k,
+        contiguous_ld(layout, b0, b1),
         beta,
         c.to_slice_mut(),
-        m,
+        contiguous_ld(layout, m.try_into().unwrap(), n),
     );
 }
 
@@ -482,7 +717,8 @@ pub fn gemm<E: CpuGemm>(
     // shape len check
     if a.shape.len() != 2 || b.shape.len() != 2 || c.shape.len() != 2
         // input shape shape is collect check
-        || a.shape[0] != c.shape[0] || b.shape[0] != a.shape[1] || b.shape[1] != c.shape[1]
+        || op_shape(&transa, &a).0 != c.shape[0] || op_shape(&transb, &b).0 != op_shape(&transa, &a).1
+        || op_shape(&transb, &b).1 != c.shape[1]
         // a, b and c must be column major
         || !c.is_column_major() || !a.is_column_major() || !b.is_column_major() ||
         // a, b, and c's smallest stride must be 1
@@ -496,6 +732,240 @@ pub fn gemm<E: CpuGemm>(
     }
 }
 
+/// エルミート行列Aと一般行列Bの積を計算します。
+/// C := alpha * A B + beta * C (sideがLeft)
+/// C := alpha * B A + beta * C (sideがRight)
+/// Aはuploで指定した側の三角部分だけが参照されます。
+/// 行列は全て連続したものとして扱います。
+#[allow(clippy::too_many_arguments)]
+pub fn hemm_unchecked<E: CpuHemm>(
+    layout: CpuLayout,
+    side: CpuSide,
+    uplo: CpuPart,
+    alpha: E,
+    beta: E,
+    a: CpuViewTensor<E>,
+    b: CpuViewTensor<E>,
+    mut c: CpuViewMutTensor<E>,
+) {
+    let m = c.shape_vec()[0].try_into().unwrap();
+    let n = c.shape_vec()[1].try_into().unwrap();
+    let ka = a.shape_vec()[0].try_into().unwrap();
+    let ld = contiguous_ld(layout, m, n);
+    E::cpu_hemm(
+        layout.into(),
+        side.into(),
+        uplo.into(),
+        m,
+        n,
+        alpha,
+        a.to_slice(),
+        ka,
+        b.to_slice(),
+        ld,
+        beta,
+        c.to_slice_mut(),
+        ld,
+    );
+}
+
+/// エルミート行列Aと一般行列Bの積を計算します。
+/// C := alpha * A B + beta * C (sideがLeft)
+/// C := alpha * B A + beta * C (sideがRight)
+/// Aはuploで指定した側の三角部分だけが参照されます。
+/// A, B, Cのメモリの並びは揃っている必要があります。
+pub fn hemm<E: CpuHemm>(
+    side: CpuSide,
+    uplo: CpuPart,
+    alpha: E,
+    beta: E,
+    a: CpuViewTensor<E>,
+    b: CpuViewTensor<E>,
+    mut c: CpuViewMutTensor<E>,
+) -> Option<()> {
+    if a.shape.num_dim() != 2 || b.shape.num_dim() != 2 || c.shape.num_dim() != 2 {
+        return None;
+    }
+    let k = match side {
+        CpuSide::Left => c.shape[0],
+        CpuSide::Right => c.shape[1],
+    };
+    if a.shape[0] != k || a.shape[1] != k || b.shape != c.shape {
+        return None;
+    }
+    let (layout, lda) = matrix_layout(&a)?;
+    let (layout_b, ldb) = matrix_layout(&b)?;
+    let (layout_c, ldc) = matrix_layout(&c)?;
+    if layout != layout_b || layout != layout_c {
+        return None;
+    }
+    E::cpu_hemm(
+        layout.into(),
+        side.into(),
+        uplo.into(),
+        c.shape[0].try_into().unwrap(),
+        c.shape[1].try_into().unwrap(),
+        alpha,
+        a.to_slice(),
+        lda,
+        b.to_slice(),
+        ldb,
+        beta,
+        c.to_slice_mut(),
+        ldc,
+    );
+    Some(())
+}
+
+/// エルミート行列のrank-k更新を計算します。
+/// C := alpha * A A^H + beta * C (transがNone)
+/// C := alpha * A^H A + beta * C (transがConjugate)
+/// alpha, betaは実数で、Cはuploで指定した側の三角部分だけが更新されます。
+/// 行列は全て連続したものとして扱います。
+#[allow(clippy::too_many_arguments)]
+pub fn herk_unchecked<E: CpuHerk>(
+    layout: CpuLayout,
+    uplo: CpuPart,
+    trans: CpuTranspose,
+    alpha: E::Real,
+    beta: E::Real,
+    a: CpuViewTensor<E>,
+    mut c: CpuViewMutTensor<E>,
+) {
+    let n = c.shape_vec()[0].try_into().unwrap();
+    let shape_a = a.shape_vec();
+    let (rows, cols) = (
+        shape_a[0].try_into().unwrap(),
+        shape_a[1].try_into().unwrap(),
+    );
+    let k = match trans {
+        CpuTranspose::None => cols,
+        _ => rows,
+    };
+    E::cpu_herk(
+        layout.into(),
+        uplo.into(),
+        trans.into(),
+        n,
+        k,
+        alpha,
+        a.to_slice(),
+        contiguous_ld(layout, rows, cols),
+        beta,
+        c.to_slice_mut(),
+        n,
+    );
+}
+
+/// エルミート行列のrank-k更新を計算します。
+/// C := alpha * A A^H + beta * C (transがNone)
+/// C := alpha * A^H A + beta * C (transがConjugate)
+/// alpha, betaは実数で、Cはuploで指定した側の三角部分だけが更新されます。
+/// transに`CpuTranspose::Ordinary`は指定できません。
+pub fn herk<E: CpuHerk>(
+    uplo: CpuPart,
+    trans: CpuTranspose,
+    alpha: E::Real,
+    beta: E::Real,
+    a: CpuViewTensor<E>,
+    mut c: CpuViewMutTensor<E>,
+) -> Option<()> {
+    if a.shape.num_dim() != 2 || c.shape.num_dim() != 2 || c.shape[0] != c.shape[1] {
+        return None;
+    }
+    let (n, k) = match trans {
+        CpuTranspose::None => (a.shape[0], a.shape[1]),
+        CpuTranspose::Conjugate => (a.shape[1], a.shape[0]),
+        CpuTranspose::Ordinary => return None,
+    };
+    if n != c.shape[0] {
+        return None;
+    }
+    let (layout, lda) = matrix_layout(&a)?;
+    let (layout_c, ldc) = matrix_layout(&c)?;
+    if layout != layout_c {
+        return None;
+    }
+    E::cpu_herk(
+        layout.into(),
+        uplo.into(),
+        trans.into(),
+        n.try_into().unwrap(),
+        k.try_into().unwrap(),
+        alpha,
+        a.to_slice(),
+        lda,
+        beta,
+        c.to_slice_mut(),
+        ldc,
+    );
+    Some(())
+}
+
+/// 同じshape, strideのまま要素をf32に変換する。strideは負であってはいけない。
+/// shape, strideが指す要素だけを変換し、間の要素は0にする。
+fn to_f32_layout<P, E>(a: &TensorBase<P, E>) -> CpuTensor<f32>
+where
+    P: TensorPointer<Elem = E> + Cpu,
+    E: Cast<f32>,
+{
+    let len = if a.shape.num_elms() == 0 {
+        0
+    } else {
+        (0..a.shape.num_dim())
+            .map(|i| ((a.shape[i] - 1) * a.stride[i]) as usize)
+            .sum::<usize>()
+            + 1
+    };
+    let mut a32 = TensorBase {
+        ptr: OwnedCpu::from_vec(vec![0.; len]),
+        shape: a.shape.clone(),
+        stride: a.stride.clone(),
+        num_elm: len,
+    };
+    for (x, y) in a32.iter_mut().zip(a.iter()) {
+        *x = y.cast();
+    }
+    a32
+}
+
+/// f16, bf16などの行列積を計算します。
+/// f32に変換してから`gemm`を計算し、結果をcの型に戻して書き込みます。
+pub fn gemm_via_f32<E>(
+    transa: CpuTranspose,
+    transb: CpuTranspose,
+    alpha: f32,
+    beta: f32,
+    a: CpuViewTensor<E>,
+    b: CpuViewTensor<E>,
+    mut c: CpuViewMutTensor<E>,
+) -> Option<()>
+where
+    E: Cast<f32>,
+    f32: Cast<E>,
+{
+    // 負のstrideなどgemmが受け付けない行列は変換する前に弾く
+    matrix_layout(&a)?;
+    matrix_layout(&b)?;
+    matrix_layout(&c)?;
+    let a32 = to_f32_layout(&a);
+    let b32 = to_f32_layout(&b);
+    let mut c32 = to_f32_layout(&c);
+    gemm(
+        transa,
+        transb,
+        alpha,
+        beta,
+        a32.to_view(),
+        b32.to_view(),
+        c32.to_view_mut(),
+    )?;
+    for (x, y) in c.iter_mut().zip(c32.iter()) {
+        *x = y.cast();
+    }
+    Some(())
+}
+
 #[test]
 fn asum_test_f32() {
     use crate::shape::Shape;
@@ -866,3 +1336,242 @@ fn asum_f64_test_index() {
     let res = asum(a.slice(index![1, ..])).unwrap();
     assert_eq!(res, 12.);
 }
+
+#[test]
+fn gemm_via_f32_test_f16() {
+    use super::CpuTranspose;
+    use crate::shape::Shape;
+    use half::f16;
+    let a = vec![1., 2., 3., 4., 5., 6.];
+    let b = vec![1., 2.];
+    let c = vec![1., 1., 1.];
+    let a32 = CpuTensor::from_vec(a, Shape::new(vec![3, 2]));
+    let b32 = CpuTensor::from_vec(b, Shape::new(vec![2, 1]));
+    let mut c32 = CpuTensor::from_vec(c, Shape::new(vec![3, 1]));
+    let a16 = a32.cast::<f16>();
+    let b16 = b32.cast::<f16>();
+    let mut c16 = c32.cast::<f16>();
+    gemm(
+        CpuTranspose::None,
+        CpuTranspose::None,
+        2.,
+        1.,
+        a32.to_view(),
+        b32.to_view(),
+        c32.to_view_mut(),
+    )
+    .unwrap();
+    gemm_via_f32(
+        CpuTranspose::None,
+        CpuTranspose::None,
+        2.,
+        1.,
+        a16.to_view(),
+        b16.to_view(),
+        c16.to_view_mut(),
+    )
+    .unwrap();
+    assert_eq!(c16.cast::<f32>().to_vec(), c32.to_vec());
+}
+
+#[test]
+fn gemm_via_f32_test_sliced_view() {
+    use super::CpuTranspose;
+    use crate::index;
+    use crate::shape::Shape;
+    use half::f16;
+    let a32 = CpuTensor::from_vec((0..8).map(|x| x as f32).collect(), Shape::new(vec![4, 2]));
+    let b32 = CpuTensor::from_vec(vec![1., 2.], Shape::new(vec![2, 1]));
+    let mut c32 = CpuTensor::from_vec(vec![1., 2., 3., 4.], Shape::new(vec![4, 1]));
+    let a16 = a32.cast::<f16>();
+    let b16 = b32.cast::<f16>();
+    let mut c16 = c32.cast::<f16>();
+    // 先頭を飛ばしたaと末尾を残したcのviewを渡す
+    gemm(
+        CpuTranspose::None,
+        CpuTranspose::None,
+        1.,
+        1.,
+        a32.slice(index![1.., ..]),
+        b32.to_view(),
+        c32.slice_mut(index![..3, ..]),
+    )
+    .unwrap();
+    gemm_via_f32(
+        CpuTranspose::None,
+        CpuTranspose::None,
+        1.,
+        1.,
+        a16.slice(index![1.., ..]),
+        b16.to_view(),
+        c16.slice_mut(index![..3, ..]),
+    )
+    .unwrap();
+    assert_eq!(c16.cast::<f32>().to_vec(), c32.to_vec());
+    assert_eq!(c32.to_vec()[3], 4.);
+}
+
+#[test]
+fn complex_level1_test_c32() {
+    use crate::shape::Shape;
+    use num_complex::Complex32;
+    let x = vec![Complex32::new(1., 2.), Complex32::new(3., -4.)];
+    let y = vec![Complex32::new(0., 1.), Complex32::new(2., 1.)];
+    let x = CpuTensor::from_vec(x, Shape::new(vec![2]));
+    let mut y = CpuTensor::from_vec(y, Shape::new(vec![2]));
+    assert_eq!(asum(x.to_view()).unwrap(), 10.);
+    assert_eq!(nrm2(x.to_view()).unwrap(), 30f32.sqrt());
+    // (1+2i)i + (3-4i)(2+i) = (-2+i) + (10-5i)
+    assert_eq!(
+        dotu(x.to_view(), y.to_view()).unwrap(),
+        Complex32::new(8., -4.)
+    );
+    // (1-2i)i + (3+4i)(2+i) = (2+i) + (2+11i)
+    assert_eq!(
+        dotc(x.to_view(), y.to_view()).unwrap(),
+        Complex32::new(4., 12.)
+    );
+    axpy(Complex32::new(0., 1.), x.to_view(), y.to_view_mut()).unwrap();
+    assert_eq!(
+        y.to_vec(),
+        vec![Complex32::new(-2., 2.), Complex32::new(6., 4.)]
+    );
+}
+
+#[test]
+fn complex_ger_test_c64() {
+    use crate::shape::Shape;
+    use num_complex::Complex64;
+    let x = CpuTensor::from_vec(
+        vec![Complex64::new(1., 1.), Complex64::new(0., 2.)],
+        Shape::new(vec![2]),
+    );
+    let y = CpuTensor::from_vec(vec![Complex64::new(1., -1.)], Shape::new(vec![1]));
+    let zeros = vec![Complex64::new(0., 0.); 2];
+    let mut a = CpuTensor::from_vec(zeros.clone(), Shape::new(vec![2, 1]));
+    geru(
+        Complex64::new(1., 0.),
+        x.to_view(),
+        y.to_view(),
+        a.to_view_mut(),
+    )
+    .unwrap();
+    assert_eq!(
+        a.to_vec(),
+        vec![Complex64::new(2., 0.), Complex64::new(2., 2.)]
+    );
+    let mut a = CpuTensor::from_vec(zeros, Shape::new(vec![2, 1]));
+    gerc(
+        Complex64::new(1., 0.),
+        x.to_view(),
+        y.to_view(),
+        a.to_view_mut(),
+    )
+    .unwrap();
+    assert_eq!(
+        a.to_vec(),
+        vec![Complex64::new(0., 2.), Complex64::new(-2., 2.)]
+    );
+}
+
+#[test]
+fn complex_gemm_conjugate_test_c32() {
+    use super::{CpuLayout, CpuTranspose};
+    use crate::shape::Shape;
+    use num_complex::Complex32;
+    let a = CpuTensor::from_vec(
+        vec![Complex32::new(1., 1.), Complex32::new(2., -1.)],
+        Shape::new(vec![2, 1]),
+    );
+    let mut c = CpuTensor::from_vec(vec![Complex32::new(0., 0.)], Shape::new(vec![1, 1]));
+    // A^H A = |1+i|^2 + |2-i|^2
+    gemm_unchecked(
+        CpuLayout::ColumnMajor,
+        CpuTranspose::Conjugate,
+        CpuTranspose::None,
+        Complex32::new(1., 0.),
+        Complex32::new(0., 0.),
+        a.to_view(),
+        a.to_view(),
+        c.to_view_mut(),
+    );
+    assert_eq!(c.to_vec(), vec![Complex32::new(7., 0.)]);
+    gemm_unchecked(
+        CpuLayout::ColumnMajor,
+        CpuTranspose::Ordinary,
+        CpuTranspose::None,
+        Complex32::new(1., 0.),
+        Complex32::new(0., 0.),
+        a.to_view(),
+        a.to_view(),
+        c.to_view_mut(),
+    );
+    assert_eq!(c.to_vec(), vec![Complex32::new(3., -2.)]);
+}
+
+#[test]
+fn hemm_herk_test_c64() {
+    use super::{CpuPart, CpuSide, CpuTranspose};
+    use crate::shape::Shape;
+    use num_complex::Complex64;
+    let z = Complex64::new(0., 0.);
+    // 上三角だけを使う。下三角の値は参照されない
+    let a = CpuTensor::from_vec(
+        vec![
+            Complex64::new(2., 0.),
+            Complex64::new(1., 1.),
+            Complex64::new(9., 9.),
+            Complex64::new(3., 0.),
+        ],
+        Shape::new(vec![2, 2]),
+    );
+    let b = CpuTensor::from_vec(
+        vec![Complex64::new(1., 0.), Complex64::new(0., 1.)],
+        Shape::new(vec![2, 1]),
+    );
+    let mut c = CpuTensor::from_vec(vec![z; 2], Shape::new(vec![2, 1]));
+    hemm(
+        CpuSide::Left,
+        CpuPart::Upper,
+        Complex64::new(1., 0.),
+        z,
+        a.to_view(),
+        b.to_view(),
+        c.to_view_mut(),
+    )
+    .unwrap();
+    // [[2, 1+i], [1-i, 3]] [1, i]^T
+    assert_eq!(
+        c.to_vec(),
+        vec![Complex64::new(1., 1.), Complex64::new(1., 2.)]
+    );
+    let mut c = CpuTensor::from_vec(vec![z; 4], Shape::new(vec![2, 2]));
+    herk(
+        CpuPart::Lower,
+        CpuTranspose::None,
+        1.,
+        0.,
+        b.to_view(),
+        c.to_view_mut(),
+    )
+    .unwrap();
+    // b b^H の下三角だけが書き込まれる
+    assert_eq!(
+        c.to_vec(),
+        vec![
+            Complex64::new(1., 0.),
+            z,
+            Complex64::new(0., 1.),
+            Complex64::new(1., 0.)
+        ]
+    );
+    assert!(herk(
+        CpuPart::Lower,
+        CpuTranspose::Ordinary,
+        1.,
+        0.,
+        b.to_view(),
+        c.to_view_mut()
+    )
+    .is_none());
+}
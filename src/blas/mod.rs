@@ -1,10 +1,11 @@
 use std::convert::From;
 
-use cblas::{Layout, Transpose};
+use cblas::{Layout, Part, Side, Transpose};
 
 pub mod cpu;
 
 #[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum CpuLayout {
     RowMajor,
     ColumnMajor,
@@ -16,6 +17,18 @@ pub enum CpuTranspose {
     Conjugate,
 }
 
+/// 対称行列やエルミート行列を左右どちらから掛けるか
+pub enum CpuSide {
+    Left,
+    Right,
+}
+
+/// 対称行列やエルミート行列の上三角と下三角のどちらを使うか
+pub enum CpuPart {
+    Upper,
+    Lower,
+}
+
 impl From<CpuLayout> for Layout {
     #[inline]
     fn from(item: CpuLayout) -> Layout {
@@ -36,3 +49,23 @@ impl From<CpuTranspose> for Transpose {
         }
     }
 }
+
+impl From<CpuSide> for Side {
+    #[inline]
+    fn from(item: CpuSide) -> Side {
+        match item {
+            CpuSide::Left => Side::Left,
+            CpuSide::Right => Side::Right,
+        }
+    }
+}
+
+impl From<CpuPart> for Part {
+    #[inline]
+    fn from(item: CpuPart) -> Part {
+        match item {
+            CpuPart::Upper => Part::Upper,
+            CpuPart::Lower => Part::Lower,
+        }
+    }
+}
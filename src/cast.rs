@@ -0,0 +1,299 @@
+use half::{bf16, f16};
+
+use crate::memory_pool::collect_vec;
+use crate::pointer_traits::{Cpu, TensorPointer};
+use crate::tensor::{CpuTensor, TensorBase};
+
+/// 要素の型の変換。`TensorBase::cast`で使う。
+///
+/// 変換の規則は以下の通り。
+/// * 浮動小数点数から整数: 0方向に丸め、範囲外は最小値か最大値に飽和する。NaNは0になる。
+/// * 整数から整数: 範囲外は最小値か最大値に飽和する。
+/// * 整数や`f64`から`f32`などの精度の低い浮動小数点数: 最も近い値に丸める(偶数丸め)。範囲外は無限大になる。
+/// * `bool`から数値: `false`は0、`true`は1になる。
+/// * 数値から`bool`: 0以外(NaNを含む)は`true`になる。
+/// * `f16`, `bf16`は`f32`を経由して変換する。ただし整数からは二重に丸めないよう、直接最も近い値に丸める。
+pub trait Cast<T>: Copy {
+    fn cast(self) -> T;
+}
+
+macro_rules! impl_cast_as {
+    ( $from:ty => $( $to:ty ),* ) => {
+        $(
+            impl Cast<$to> for $from {
+                #[inline]
+                fn cast(self) -> $to {
+                    self as $to
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_cast_saturate {
+    ( $from:ty => $( $to:ty ),* ) => {
+        $(
+            impl Cast<$to> for $from {
+                #[inline]
+                fn cast(self) -> $to {
+                    (self as i128).clamp(<$to>::MIN as i128, <$to>::MAX as i128) as $to
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_cast_bool {
+    ( $( $ty:ty ),* ) => {
+        $(
+            impl Cast<bool> for $ty {
+                #[inline]
+                fn cast(self) -> bool {
+                    self != <$ty>::default()
+                }
+            }
+
+            impl Cast<$ty> for bool {
+                #[inline]
+                fn cast(self) -> $ty {
+                    self as u8 as $ty
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_cast_half {
+    ( $half:ty => $( $ty:ty ),* ) => {
+        $(
+            impl Cast<$ty> for $half {
+                #[inline]
+                fn cast(self) -> $ty {
+                    self.to_f32().cast()
+                }
+            }
+
+            impl Cast<$half> for $ty {
+                #[inline]
+                fn cast(self) -> $half {
+                    <$half>::from_f32(self.cast())
+                }
+            }
+        )*
+    };
+}
+
+/// 整数を仮数部が`digits`bitの浮動小数点数で表せる最も近い値に丸める(偶数丸め)。
+/// 返り値は`f64`で正確に表せる。
+fn round_int(v: i64, digits: u32) -> f64 {
+    let m = v.unsigned_abs();
+    let bits = u64::BITS - m.leading_zeros();
+    if bits <= digits {
+        return v as f64;
+    }
+    let shift = bits - digits;
+    let (q, r, half) = (m >> shift, m & ((1 << shift) - 1), 1 << (shift - 1));
+    let q = if r > half || (r == half && q & 1 == 1) {
+        q + 1
+    } else {
+        q
+    };
+    let rounded = q as f64 * 2f64.powi(shift as i32);
+    if v < 0 {
+        -rounded
+    } else {
+        rounded
+    }
+}
+
+// 整数は`f32`や`f64`を経由すると二重に丸められるため、先に半精度の桁数に丸めてから変換する
+macro_rules! impl_cast_half_int {
+    ( $half:ty => $( $ty:ty ),* ) => {
+        $(
+            impl Cast<$ty> for $half {
+                #[inline]
+                fn cast(self) -> $ty {
+                    self.to_f32().cast()
+                }
+            }
+
+            impl Cast<$half> for $ty {
+                #[inline]
+                fn cast(self) -> $half {
+                    <$half>::from_f64(round_int(self as i64, <$half>::MANTISSA_DIGITS))
+                }
+            }
+        )*
+    };
+}
+
+// rustの`as`は浮動小数点数から整数への変換で飽和し、NaNを0にする
+impl_cast_as!(f32 => f32, f64, i8, i16, i32, i64, u8);
+impl_cast_as!(f64 => f32, f64, i8, i16, i32, i64, u8);
+impl_cast_as!(i8 => f32, f64);
+impl_cast_as!(i16 => f32, f64);
+impl_cast_as!(i32 => f32, f64);
+impl_cast_as!(i64 => f32, f64);
+impl_cast_as!(u8 => f32, f64);
+impl_cast_saturate!(i8 => i8, i16, i32, i64, u8);
+impl_cast_saturate!(i16 => i8, i16, i32, i64, u8);
+impl_cast_saturate!(i32 => i8, i16, i32, i64, u8);
+impl_cast_saturate!(i64 => i8, i16, i32, i64, u8);
+impl_cast_saturate!(u8 => i8, i16, i32, i64, u8);
+impl_cast_bool!(f32, f64, i8, i16, i32, i64, u8);
+impl_cast_half!(f16 => f32, bool);
+impl_cast_half!(bf16 => f32, bool);
+impl_cast_half_int!(f16 => i8, i16, i32, i64, u8);
+impl_cast_half_int!(bf16 => i8, i16, i32, i64, u8);
+
+impl Cast<bool> for bool {
+    #[inline]
+    fn cast(self) -> bool {
+        self
+    }
+}
+
+// f64は`f32`を経由すると二重に丸められるため、直接変換する
+impl Cast<f64> for f16 {
+    #[inline]
+    fn cast(self) -> f64 {
+        self.to_f64()
+    }
+}
+
+impl Cast<f16> for f64 {
+    #[inline]
+    fn cast(self) -> f16 {
+        f16::from_f64(self)
+    }
+}
+
+impl Cast<f64> for bf16 {
+    #[inline]
+    fn cast(self) -> f64 {
+        self.to_f64()
+    }
+}
+
+impl Cast<bf16> for f64 {
+    #[inline]
+    fn cast(self) -> bf16 {
+        bf16::from_f64(self)
+    }
+}
+
+impl Cast<f16> for f16 {
+    #[inline]
+    fn cast(self) -> f16 {
+        self
+    }
+}
+
+impl Cast<bf16> for bf16 {
+    #[inline]
+    fn cast(self) -> bf16 {
+        self
+    }
+}
+
+impl Cast<bf16> for f16 {
+    #[inline]
+    fn cast(self) -> bf16 {
+        bf16::from_f32(self.to_f32())
+    }
+}
+
+impl Cast<f16> for bf16 {
+    #[inline]
+    fn cast(self) -> f16 {
+        f16::from_f32(self.to_f32())
+    }
+}
+
+impl<P, E> TensorBase<P, E>
+where
+    P: TensorPointer<Elem = E> + Cpu,
+{
+    /// 各要素を`F`に変換したtensorを返す。結果はrow majorの連続したtensorになる。
+    /// 変換の規則は`Cast`を参照。
+    pub fn cast<F>(&self) -> CpuTensor<F>
+    where
+        E: Cast<F>,
+        F: Copy,
+    {
+        let v = collect_vec(self.iter().map(|x| x.cast()));
+        CpuTensor::from_vec(v, self.shape.clone())
+    }
+}
+
+#[cfg(test)]
+use crate::shape::Shape;
+
+#[test]
+fn cast_float_to_int_test() {
+    let a = CpuTensor::from_vec(
+        vec![1.7f32, -1.7, 300., -300., f32::NAN, f32::INFINITY],
+        Shape::new(vec![6]),
+    );
+    assert_eq!(a.cast::<i8>().to_vec(), vec![1, -1, 127, -128, 0, 127]);
+    assert_eq!(a.cast::<u8>().to_vec(), vec![1, 0, 255, 0, 0, 255]);
+    assert_eq!(
+        a.cast::<bool>().to_vec(),
+        vec![true, true, true, true, true, true]
+    );
+}
+
+#[test]
+fn cast_int_test() {
+    let a = CpuTensor::from_vec(vec![-1i64, 0, 200, 70000], Shape::new(vec![2, 2]));
+    assert_eq!(a.cast::<u8>().to_vec(), vec![0, 0, 200, 255]);
+    assert_eq!(a.cast::<i16>().to_vec(), vec![-1, 0, 200, 32767]);
+    assert_eq!(a.cast::<bool>().to_vec(), vec![true, false, true, true]);
+    // strideを考慮して、row majorの結果を返す
+    let t = a.t().cast::<f64>();
+    assert_eq!(t.shape(), Shape::new(vec![2, 2]));
+    assert_eq!(t.to_vec(), vec![-1., 200., 0., 70000.]);
+    let b = CpuTensor::from_vec(vec![true, false], Shape::new(vec![2]));
+    assert_eq!(b.cast::<f32>().to_vec(), vec![1., 0.]);
+}
+
+#[test]
+fn cast_half_test() {
+    let a = CpuTensor::from_vec(vec![1.0f32, 0.1, 70000., -2.5], Shape::new(vec![4]));
+    let h = a.cast::<f16>();
+    assert_eq!(
+        h.to_vec(),
+        vec![
+            f16::from_f32(1.),
+            f16::from_f32(0.1),
+            f16::INFINITY,
+            f16::from_f32(-2.5)
+        ]
+    );
+    assert_eq!(h.cast::<i32>().to_vec(), vec![1, 0, i32::MAX, -2]);
+    let b = a.cast::<bf16>();
+    assert_eq!(b.cast::<f32>().to_vec()[2], 70144.);
+    // f32を経由すると2^24+2^16に丸められた後、偶数丸めで2^24になってしまう
+    let i = CpuTensor::from_vec(
+        vec![
+            (1i64 << 24) + (1 << 16) + 1,
+            -(1 << 24) - (1 << 16),
+            1 << 62,
+        ],
+        Shape::new(vec![3]),
+    );
+    assert_eq!(
+        i.cast::<bf16>().cast::<f64>().to_vec(),
+        vec![
+            ((1 << 24) + (1 << 17)) as f64,
+            -(1 << 24) as f64,
+            (1i64 << 62) as f64
+        ]
+    );
+    assert_eq!(i.cast::<f16>().to_vec()[0], f16::INFINITY);
+    let i = CpuTensor::from_vec(vec![2049i32, 65519, 65520], Shape::new(vec![3]));
+    assert_eq!(
+        i.cast::<f16>().cast::<f32>().to_vec(),
+        vec![2048., 65504., f32::INFINITY]
+    );
+}
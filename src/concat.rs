@@ -0,0 +1,393 @@
+use crate::error::TensorError;
+use crate::iter::Iter;
+use crate::pointer_traits::{Owned, TensorPointer};
+use crate::shape::{checked_offset, Shape};
+use crate::tensor::{CpuTensor, CpuViewTensor, TensorBase};
+
+/// 各tensorをrow majorで辿り、外側のindex毎にそれぞれのtensorから`block`個ずつ取り出して並べる。
+fn join<E: Copy>(mut iters: Vec<Iter<'_, E>>, blocks: &[usize], outer: usize) -> Vec<E> {
+    let num_elm = blocks.iter().sum::<usize>() * outer;
+    let mut v = Vec::with_capacity(num_elm);
+    for _ in 0..outer {
+        for (iter, block) in iters.iter_mut().zip(blocks.iter()) {
+            v.extend(iter.by_ref().take(*block).copied());
+        }
+    }
+    v
+}
+
+/// 既存の軸`axis`に沿ってtensorを連結する。
+/// `axis`以外の軸の大きさは全て等しくなければならない。
+pub fn try_concat<E: Copy>(
+    tensors: &[CpuViewTensor<E>],
+    axis: usize,
+) -> Result<CpuTensor<E>, TensorError> {
+    let first = tensors.first().ok_or(TensorError::EmptyTensorList)?.shape();
+    if axis >= first.num_dim() {
+        return Err(TensorError::InvalidAxis {
+            axis,
+            num_dim: first.num_dim(),
+        });
+    }
+    for t in tensors.iter() {
+        if t.shape.num_dim() != first.num_dim()
+            || t.shape
+                .iter()
+                .zip(first.iter())
+                .enumerate()
+                .any(|(i, (a, b))| i != axis && a != b)
+        {
+            return Err(TensorError::ShapeMismatch {
+                expected: first,
+                got: t.shape(),
+            });
+        }
+    }
+
+    let mut shape = first.clone();
+    shape[axis] = tensors.iter().map(|t| t.shape[axis]).sum();
+    let outer = first[..axis].iter().product::<isize>() as usize;
+    let inner = first[axis + 1..].iter().product::<isize>() as usize;
+    let blocks = tensors
+        .iter()
+        .map(|t| t.shape[axis] as usize * inner)
+        .collect::<Vec<usize>>();
+    let iters = tensors.iter().map(|t| t.iter()).collect();
+    TensorBase::try_from_vec(join(iters, &blocks, outer), shape)
+}
+
+pub fn concat<E: Copy>(tensors: &[CpuViewTensor<E>], axis: usize) -> CpuTensor<E> {
+    try_concat(tensors, axis).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// 新しい軸`axis`を作ってtensorを積み重ねる。全てのtensorのshapeは等しくなければならない。
+pub fn try_stack<E: Copy>(
+    tensors: &[CpuViewTensor<E>],
+    axis: usize,
+) -> Result<CpuTensor<E>, TensorError> {
+    let first = tensors.first().ok_or(TensorError::EmptyTensorList)?.shape();
+    if axis > first.num_dim() {
+        return Err(TensorError::InvalidAxis {
+            axis,
+            num_dim: first.num_dim() + 1,
+        });
+    }
+    if let Some(t) = tensors.iter().find(|t| t.shape != first) {
+        return Err(TensorError::ShapeMismatch {
+            expected: first,
+            got: t.shape(),
+        });
+    }
+
+    let mut shape = first.clone();
+    shape.add_axis_unchecked(axis);
+    shape[axis] = tensors.len() as isize;
+    let outer = first[..axis].iter().product::<isize>() as usize;
+    let block = first[axis..].iter().product::<isize>() as usize;
+    let blocks = vec![block; tensors.len()];
+    let iters = tensors.iter().map(|t| t.iter()).collect();
+    TensorBase::try_from_vec(join(iters, &blocks, outer), shape)
+}
+
+pub fn stack<E: Copy>(tensors: &[CpuViewTensor<E>], axis: usize) -> CpuTensor<E> {
+    try_stack(tensors, axis).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// `axis`に沿って`sizes`の大きさに分割した時の、各部分の先頭のoffsetとshapeを返す。
+/// offsetは元のtensorの先頭からの相対的な位置で、strideが負の場合は負になる。
+fn split_offset_shape(
+    a_shape: &Shape,
+    stride: isize,
+    sizes: &[usize],
+    axis: usize,
+) -> Result<Vec<(isize, Shape)>, TensorError> {
+    if axis >= a_shape.num_dim() {
+        return Err(TensorError::InvalidAxis {
+            axis,
+            num_dim: a_shape.num_dim(),
+        });
+    }
+    if sizes.iter().sum::<usize>() != a_shape[axis] as usize {
+        return Err(TensorError::InvalidSplitSizes {
+            sizes: sizes.to_vec(),
+            dim: a_shape[axis],
+        });
+    }
+    let mut start = 0;
+    Ok(sizes
+        .iter()
+        .map(|size| {
+            let mut shape = a_shape.clone();
+            shape[axis] = *size as isize;
+            // 要素を持たない部分はメモリにアクセスしないので先頭を指しておく
+            let offset = if shape.num_elms() == 0 {
+                0
+            } else {
+                start * stride
+            };
+            start += *size as isize;
+            (offset, shape)
+        })
+        .collect())
+}
+
+/// `axis`の大きさをn個に分ける時の大きさを返す。最後の部分以外は全て同じ大きさになる。
+fn chunk_sizes(dim: isize, n: usize) -> Result<Vec<usize>, TensorError> {
+    if n == 0 {
+        return Err(TensorError::ZeroChunks);
+    }
+    let dim = dim as usize;
+    let size = dim.div_ceil(n);
+    if size == 0 {
+        return Ok(vec![0]);
+    }
+    let mut sizes = Vec::with_capacity(n);
+    let mut start = 0;
+    while start < dim {
+        sizes.push(size.min(dim - start));
+        start += size;
+    }
+    Ok(sizes)
+}
+
+impl<P, E> TensorBase<P, E>
+where
+    P: TensorPointer<Elem = E> + Owned,
+    E: Copy,
+{
+    /// `axis`に沿って`sizes`の大きさに分割したviewを返す。データはコピーされない。
+    pub fn try_split(
+        &self,
+        sizes: &[usize],
+        axis: usize,
+    ) -> Result<Vec<TensorBase<P::View<'_>, E>>, TensorError> {
+        let stride = self.stride.get(axis).copied().unwrap_or(0);
+        split_offset_shape(&self.shape, stride, sizes, axis)?
+            .into_iter()
+            .map(|(offset, shape)| {
+                let offset = checked_offset(0, offset, self.ptr.len())?;
+                Ok(TensorBase {
+                    ptr: self.ptr.to_view(offset),
+                    shape,
+                    stride: self.stride.clone(),
+                    num_elm: self.num_elm,
+                })
+            })
+            .collect()
+    }
+
+    pub fn split(&self, sizes: &[usize], axis: usize) -> Vec<TensorBase<P::View<'_>, E>> {
+        self.try_split(sizes, axis)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// `axis`に沿ってn個のviewに分割する。
+    /// 各viewの大きさは`ceil(dim / n)`で、最後のviewだけ小さくなることがある。
+    pub fn try_chunk(
+        &self,
+        n: usize,
+        axis: usize,
+    ) -> Result<Vec<TensorBase<P::View<'_>, E>>, TensorError> {
+        let dim = self.shape.get(axis).copied().unwrap_or(0);
+        self.try_split(&chunk_sizes(dim, n)?, axis)
+    }
+
+    /// `try_chunk`と同じだが、エラーの場合はpanicする。
+    pub fn chunk(&self, n: usize, axis: usize) -> Vec<TensorBase<P::View<'_>, E>> {
+        self.try_chunk(n, axis).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl<'a, E: Copy> CpuViewTensor<'a, E> {
+    /// `axis`に沿って`sizes`の大きさに分割したviewを返す。データはコピーされない。
+    pub fn try_split(
+        &self,
+        sizes: &[usize],
+        axis: usize,
+    ) -> Result<Vec<CpuViewTensor<'a, E>>, TensorError> {
+        let stride = self.stride.get(axis).copied().unwrap_or(0);
+        let base = self.ptr.offset_num();
+        split_offset_shape(&self.shape, stride, sizes, axis)?
+            .into_iter()
+            .map(|(offset, shape)| {
+                let offset = checked_offset(base, offset, base + self.ptr.len())?;
+                Ok(TensorBase {
+                    ptr: self.ptr.with_offset(offset)?,
+                    shape,
+                    stride: self.stride.clone(),
+                    num_elm: self.num_elm,
+                })
+            })
+            .collect()
+    }
+
+    pub fn split(&self, sizes: &[usize], axis: usize) -> Vec<CpuViewTensor<'a, E>> {
+        self.try_split(sizes, axis)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// `axis`に沿ってn個のviewに分割する。
+    /// 各viewの大きさは`ceil(dim / n)`で、最後のviewだけ小さくなることがある。
+    pub fn try_chunk(
+        &self,
+        n: usize,
+        axis: usize,
+    ) -> Result<Vec<CpuViewTensor<'a, E>>, TensorError> {
+        let dim = self.shape.get(axis).copied().unwrap_or(0);
+        self.try_split(&chunk_sizes(dim, n)?, axis)
+    }
+
+    /// `try_chunk`と同じだが、エラーの場合はpanicする。
+    pub fn chunk(&self, n: usize, axis: usize) -> Vec<CpuViewTensor<'a, E>> {
+        self.try_chunk(n, axis).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+#[cfg(test)]
+use crate::index;
+
+#[test]
+fn concat_axis_0() {
+    let a = CpuTensor::from_vec(vec![0, 1, 2, 3], Shape::new(vec![2, 2]));
+    let b = CpuTensor::from_vec(vec![4, 5], Shape::new(vec![1, 2]));
+    let c = concat(&[a.to_view(), b.to_view()], 0);
+    assert_eq!(c.shape(), Shape::new(vec![3, 2]));
+    assert_eq!(c.to_vec(), vec![0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn concat_axis_1_strided() {
+    let a = CpuTensor::from_vec(vec![0, 1, 2, 3], Shape::new(vec![2, 2]));
+    let b = CpuTensor::from_vec(vec![4, 5, 6, 7, 8, 9], Shape::new(vec![3, 2]));
+    let c = concat(&[a.to_view(), b.t()], 1);
+    assert_eq!(c.shape(), Shape::new(vec![2, 5]));
+    assert_eq!(c.to_vec(), vec![0, 1, 4, 6, 8, 2, 3, 5, 7, 9]);
+}
+
+#[test]
+#[should_panic]
+fn concat_shape_mismatch() {
+    let a = CpuTensor::from_vec(vec![0, 1, 2, 3], Shape::new(vec![2, 2]));
+    let b = CpuTensor::from_vec(vec![4, 5, 6], Shape::new(vec![1, 3]));
+    let _ = concat(&[a.to_view(), b.to_view()], 0);
+}
+
+#[test]
+fn stack_test() {
+    let a = CpuTensor::from_vec(vec![0, 1, 2], Shape::new(vec![3]));
+    let b = CpuTensor::from_vec(vec![3, 4, 5], Shape::new(vec![3]));
+    let c = stack(&[a.to_view(), b.to_view()], 0);
+    assert_eq!(c.shape(), Shape::new(vec![2, 3]));
+    assert_eq!(c.to_vec(), vec![0, 1, 2, 3, 4, 5]);
+    let c = stack(&[a.to_view(), b.to_view()], 1);
+    assert_eq!(c.shape(), Shape::new(vec![3, 2]));
+    assert_eq!(c.to_vec(), vec![0, 3, 1, 4, 2, 5]);
+}
+
+#[test]
+fn split_test() {
+    let a = CpuTensor::from_vec((0..12).collect(), Shape::new(vec![4, 3]));
+    let parts = a.split(&[1, 3], 0);
+    assert_eq!(parts[0].shape(), Shape::new(vec![1, 3]));
+    assert_eq!(parts[1].shape(), Shape::new(vec![3, 3]));
+    assert_eq!(
+        parts[1].iter().copied().collect::<Vec<i32>>(),
+        (3..12).collect::<Vec<i32>>()
+    );
+    let parts = a.split(&[2, 1], 1);
+    assert_eq!(
+        parts[0].iter().copied().collect::<Vec<i32>>(),
+        vec![0, 1, 3, 4, 6, 7, 9, 10]
+    );
+    assert_eq!(
+        parts[1].iter().copied().collect::<Vec<i32>>(),
+        vec![2, 5, 8, 11]
+    );
+}
+
+#[test]
+fn chunk_test() {
+    let a = CpuTensor::from_vec((0..10).collect(), Shape::new(vec![5, 2]));
+    let parts = a.chunk(3, 0);
+    assert_eq!(parts.len(), 3);
+    assert_eq!(parts[2].shape(), Shape::new(vec![1, 2]));
+    assert_eq!(parts[2].iter().copied().collect::<Vec<i32>>(), vec![8, 9]);
+    assert_eq!(a.try_chunk(0, 0).err(), Some(TensorError::ZeroChunks));
+    assert_eq!(
+        a.to_view().try_chunk(0, 1).err(),
+        Some(TensorError::ZeroChunks)
+    );
+    let parts = a.to_view().chunk(2, 1);
+    assert_eq!(
+        parts[1].iter().copied().collect::<Vec<i32>>(),
+        vec![1, 3, 5, 7, 9]
+    );
+}
+
+#[test]
+#[should_panic]
+fn split_size_mismatch() {
+    let a = CpuTensor::from_vec((0..12).collect::<Vec<i32>>(), Shape::new(vec![4, 3]));
+    let _ = a.split(&[1, 2], 0);
+}
+
+#[test]
+fn try_concat_error() {
+    let a = CpuTensor::from_vec(vec![0, 1, 2, 3], Shape::new(vec![2, 2]));
+    let b = CpuTensor::from_vec(vec![4, 5, 6], Shape::new(vec![1, 3]));
+    assert_eq!(
+        try_concat(&[a.to_view(), b.to_view()], 0).err(),
+        Some(TensorError::ShapeMismatch {
+            expected: Shape::new(vec![2, 2]),
+            got: Shape::new(vec![1, 3]),
+        })
+    );
+    assert_eq!(
+        try_stack::<i32>(&[], 0).err(),
+        Some(TensorError::EmptyTensorList)
+    );
+    assert!(a.try_split(&[1, 2], 0).is_err());
+}
+
+#[test]
+fn split_chunk_reversed_test() {
+    let a = CpuTensor::from_vec((0..12).collect::<Vec<i32>>(), Shape::new(vec![4, 3]));
+    let r = a.slice(index![..;-1, ..]);
+    let parts = r.split(&[1, 3], 0);
+    assert_eq!(
+        parts[0].iter().copied().collect::<Vec<_>>(),
+        vec![9, 10, 11]
+    );
+    assert_eq!(
+        parts[1].iter().copied().collect::<Vec<_>>(),
+        vec![6, 7, 8, 3, 4, 5, 0, 1, 2]
+    );
+    let parts = r.chunk(2, 0);
+    assert_eq!(
+        parts[1].iter().copied().collect::<Vec<_>>(),
+        vec![3, 4, 5, 0, 1, 2]
+    );
+    let parts = a.slice(index![.., ..;-1]).chunk(3, 1);
+    assert_eq!(
+        parts[0].iter().copied().collect::<Vec<_>>(),
+        vec![2, 5, 8, 11]
+    );
+    assert_eq!(
+        parts[2].iter().copied().collect::<Vec<_>>(),
+        vec![0, 3, 6, 9]
+    );
+}
+
+#[test]
+fn empty_concat_split_test() {
+    let a = CpuTensor::<i32>::zeros(Shape::new(vec![0, 3]));
+    let c = concat(&[a.to_view(), a.to_view()], 1);
+    assert_eq!(c.shape(), Shape::new(vec![0, 6]));
+    let s = stack(&[a.to_view(), a.to_view()], 0);
+    assert_eq!(s.shape(), Shape::new(vec![2, 0, 3]));
+    let parts = a.chunk(2, 1);
+    assert_eq!(parts[0].shape(), Shape::new(vec![0, 2]));
+    assert_eq!(parts[1].shape(), Shape::new(vec![0, 1]));
+    let parts = a.to_view().split(&[1, 2], 1);
+    assert_eq!(parts[1].shape(), Shape::new(vec![0, 2]));
+}
@@ -0,0 +1,282 @@
+use half::{bf16, f16};
+use num_traits::{Float, One, Zero};
+
+use crate::memory_pool::alloc_vec;
+use crate::pointer_traits::TensorPointer;
+use crate::shape::Shape;
+use crate::tensor::{CpuTensor, TensorBase};
+
+/// `arange`で使える要素の型。
+/// 整数では途中の計算がoverflowしないように、浮動小数点数では誤差が溜まらないように要素を計算する。
+pub trait ArangeElement: Copy + PartialOrd + Zero {
+    /// `start`から`end`の手前まで`step`ずつ増やした時の要素数
+    fn arange_len(start: Self, end: Self, step: Self) -> usize;
+    /// `start + i * step`
+    fn arange_nth(start: Self, step: Self, i: usize) -> Self;
+}
+
+macro_rules! impl_arange_int {
+    ( $( $ty:ty ),* ) => {
+        $(
+            impl ArangeElement for $ty {
+                fn arange_len(start: Self, end: Self, step: Self) -> usize {
+                    // i128なら64bit以下の整数の差や和がoverflowしない
+                    let (start, end, step) = (start as i128, end as i128, step as i128);
+                    let len = if step > 0 {
+                        (end - start + step - 1) / step
+                    } else {
+                        (start - end - step - 1) / -step
+                    };
+                    len.max(0) as usize
+                }
+
+                fn arange_nth(start: Self, step: Self, i: usize) -> Self {
+                    (start as i128 + i as i128 * step as i128) as $ty
+                }
+            }
+        )*
+    };
+}
+
+/// 浮動小数点数の`arange`の要素数
+fn float_arange_len(start: f64, end: f64, step: f64) -> usize {
+    if !(start.is_finite() && end.is_finite() && step.is_finite()) {
+        panic!("arange start, end and step must be finite");
+    }
+    let len = ((end - start) / step).ceil();
+    if len > isize::MAX as f64 {
+        panic!("arange has too many elements");
+    }
+    len.max(0.) as usize
+}
+
+macro_rules! impl_arange_float {
+    ( $( $ty:ty ),* ) => {
+        $(
+            impl ArangeElement for $ty {
+                fn arange_len(start: Self, end: Self, step: Self) -> usize {
+                    float_arange_len(start as f64, end as f64, step as f64)
+                }
+
+                fn arange_nth(start: Self, step: Self, i: usize) -> Self {
+                    start + i as $ty * step
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_arange_half {
+    ( $( $ty:ty ),* ) => {
+        $(
+            impl ArangeElement for $ty {
+                fn arange_len(start: Self, end: Self, step: Self) -> usize {
+                    float_arange_len(start.to_f64(), end.to_f64(), step.to_f64())
+                }
+
+                /// `f32`で計算してから丸める
+                fn arange_nth(start: Self, step: Self, i: usize) -> Self {
+                    <$ty>::from_f32(start.to_f32() + i as f32 * step.to_f32())
+                }
+            }
+        )*
+    };
+}
+
+impl_arange_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+impl_arange_float!(f32, f64);
+impl_arange_half!(f16, bf16);
+
+impl<E: Copy> CpuTensor<E> {
+    /// 全ての要素がvのtensorを作る。
+    pub fn full(shape: Shape, v: E) -> Self {
+        let num_elm = shape.num_elms();
+        let mut vec = alloc_vec(num_elm);
+        vec.resize(num_elm, v);
+        Self::from_vec(vec, shape)
+    }
+
+    /// 全ての要素が0のtensorを作る。
+    pub fn zeros(shape: Shape) -> Self
+    where
+        E: Zero,
+    {
+        Self::full(shape, E::zero())
+    }
+
+    /// 全ての要素が1のtensorを作る。
+    pub fn ones(shape: Shape) -> Self
+    where
+        E: One,
+    {
+        Self::full(shape, E::one())
+    }
+
+    /// aと同じshapeで、全ての要素が0のtensorを作る。
+    pub fn zeros_like<P: TensorPointer<Elem = E>>(a: &TensorBase<P, E>) -> Self
+    where
+        E: Zero,
+    {
+        Self::zeros(a.shape())
+    }
+
+    /// aと同じshapeで、全ての要素が1のtensorを作る。
+    pub fn ones_like<P: TensorPointer<Elem = E>>(a: &TensorBase<P, E>) -> Self
+    where
+        E: One,
+    {
+        Self::ones(a.shape())
+    }
+
+    /// n x nの単位行列を作る。
+    pub fn eye(n: usize) -> Self
+    where
+        E: Zero + One,
+    {
+        let mut v = vec![E::zero(); n * n];
+        for i in 0..n {
+            v[i * n + i] = E::one();
+        }
+        Self::from_vec(v, Shape::new(vec![n as isize, n as isize]))
+    }
+
+    /// `start`から`end`の手前まで`step`ずつ増やした1次元のtensorを作る。NumPyの`arange`と同じ。
+    /// 要素数は先に`ceil((end - start) / step)`で求め、各要素は`start + i * step`で計算する。
+    /// `step`が0の場合や、`start`, `end`, `step`が無限大やNaNの場合はpanicする。
+    pub fn arange(start: E, end: E, step: E) -> Self
+    where
+        E: ArangeElement,
+    {
+        if step == E::zero() {
+            panic!("arange step must not be zero");
+        }
+        let len = E::arange_len(start, end, step);
+        let mut v = alloc_vec(len);
+        v.extend((0..len).map(|i| E::arange_nth(start, step, i)));
+        Self::from_vec(v, Shape::new(vec![len as isize]))
+    }
+
+    /// `start`から`end`まで(`end`を含む)を等間隔に`num`個に分けた1次元のtensorを作る。
+    pub fn linspace(start: E, end: E, num: usize) -> Self
+    where
+        E: Float,
+    {
+        let v = match num {
+            0 => vec![],
+            1 => vec![start],
+            _ => {
+                let div = E::from(num - 1).unwrap();
+                let step = (end - start) / div;
+                let mut v = (0..num - 1)
+                    .map(|i| start + E::from(i).unwrap() * step)
+                    .collect::<Vec<E>>();
+                v.push(end);
+                v
+            }
+        };
+        Self::from_vec(v, Shape::new(vec![num as isize]))
+    }
+
+    /// 各要素の多次元indexを`f`に渡して、その返り値を要素にしたtensorを作る。
+    /// `f`は論理的な順番(row major)で呼ばれる。
+    pub fn from_fn<F>(shape: Shape, mut f: F) -> Self
+    where
+        F: FnMut(&[isize]) -> E,
+    {
+        let num_elm = shape.num_elms();
+        let mut index = vec![0; shape.num_dim()];
+        let mut v = Vec::with_capacity(num_elm);
+        for _ in 0..num_elm {
+            v.push(f(&index));
+            for axis in (0..shape.num_dim()).rev() {
+                index[axis] += 1;
+                if index[axis] < shape[axis] {
+                    break;
+                }
+                index[axis] = 0;
+            }
+        }
+        Self::from_vec(v, shape)
+    }
+}
+
+#[test]
+fn zeros_ones_full_test() {
+    let a = CpuTensor::<f32>::zeros(Shape::new(vec![2, 3]));
+    assert_eq!(a.to_vec(), vec![0.; 6]);
+    let b = CpuTensor::ones_like(&a.t());
+    assert_eq!(b.shape(), Shape::new(vec![3, 2]));
+    assert_eq!(b.to_vec(), vec![1.; 6]);
+    let c = CpuTensor::full(Shape::new(vec![2]), 7u8);
+    assert_eq!(c.to_vec(), vec![7, 7]);
+    let d = CpuTensor::zeros_like(&c);
+    assert_eq!(d.to_vec(), vec![0, 0]);
+}
+
+#[test]
+fn eye_test() {
+    let a = CpuTensor::<i64>::eye(3);
+    assert_eq!(a.shape(), Shape::new(vec![3, 3]));
+    assert_eq!(a.to_vec(), vec![1, 0, 0, 0, 1, 0, 0, 0, 1]);
+}
+
+#[test]
+fn arange_test() {
+    assert_eq!(CpuTensor::arange(0, 5, 1).to_vec(), vec![0, 1, 2, 3, 4]);
+    assert_eq!(CpuTensor::arange(5, 0, -2).to_vec(), vec![5, 3, 1]);
+    let a = CpuTensor::arange(0., 1., 0.25);
+    assert_eq!(a.shape(), Shape::new(vec![4]));
+    assert_eq!(a.to_vec(), vec![0., 0.25, 0.5, 0.75]);
+    assert!(CpuTensor::arange(0, 0, 1).to_vec().is_empty());
+    assert!(CpuTensor::arange(0, 5, -1).to_vec().is_empty());
+}
+
+#[test]
+fn arange_int_bounds_test() {
+    // 最後の要素の次が型の範囲を超える場合もoverflowしない
+    let a = CpuTensor::arange(0i8, 127, 5).to_vec();
+    assert_eq!(a.len(), 26);
+    assert_eq!(a[25], 125);
+    let a = CpuTensor::arange(0u8, 255, 2).to_vec();
+    assert_eq!(a.len(), 128);
+    assert_eq!(a[127], 254);
+    assert_eq!(CpuTensor::arange(-100i8, 100, 1).to_vec().len(), 200);
+    assert_eq!(
+        CpuTensor::arange(i64::MAX, i64::MIN, i64::MIN).to_vec(),
+        vec![i64::MAX, -1]
+    );
+    assert_eq!(CpuTensor::arange(255u8, 0, 255).to_vec(), Vec::<u8>::new());
+}
+
+#[test]
+fn arange_half_test() {
+    use half::f16;
+    let a = CpuTensor::arange(f16::ZERO, f16::from_f32(4096.), f16::ONE);
+    assert_eq!(a.shape(), Shape::new(vec![4096]));
+    assert_eq!(a.to_vec()[3], f16::from_f32(3.));
+}
+
+#[test]
+#[should_panic(expected = "must be finite")]
+fn arange_infinite_end() {
+    CpuTensor::arange(0., f64::INFINITY, 1.);
+}
+
+#[test]
+#[should_panic(expected = "must be finite")]
+fn arange_nan_step() {
+    CpuTensor::arange(0f32, 1., f32::NAN);
+}
+
+#[test]
+fn linspace_test() {
+    let a = CpuTensor::linspace(0f64, 1., 5);
+    assert_eq!(a.to_vec(), vec![0., 0.25, 0.5, 0.75, 1.]);
+    assert_eq!(CpuTensor::linspace(2f32, 3., 1).to_vec(), vec![2.]);
+}
+
+#[test]
+fn from_fn_test() {
+    let a = CpuTensor::from_fn(Shape::new(vec![2, 3]), |idx| idx[0] * 10 + idx[1]);
+    assert_eq!(a.to_vec(), vec![0, 1, 2, 10, 11, 12]);
+}
@@ -0,0 +1,276 @@
+use std::fmt::{self, Debug, Display, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::pointer_traits::{Cpu, TensorPointer};
+use crate::tensor::{CpuCowTensor, TensorBase};
+
+static THRESHOLD: AtomicUsize = AtomicUsize::new(1000);
+static EDGE_ITEMS: AtomicUsize = AtomicUsize::new(3);
+
+/// tensorを表示する時の設定。NumPyの`set_printoptions`と同じ。
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PrintOptions {
+    /// 要素数がこれより多い場合は、各軸の先頭と末尾だけを表示して残りを`...`で省略する。
+    pub threshold: usize,
+    /// 省略する時に、各軸の先頭と末尾に表示する要素の数
+    pub edge_items: usize,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 1000,
+            edge_items: 3,
+        }
+    }
+}
+
+/// 現在の表示設定を返す。
+pub fn print_options() -> PrintOptions {
+    PrintOptions {
+        threshold: THRESHOLD.load(Ordering::Relaxed),
+        edge_items: EDGE_ITEMS.load(Ordering::Relaxed),
+    }
+}
+
+/// 全てのtensorの表示設定を変更する。
+pub fn set_print_options(options: PrintOptions) {
+    THRESHOLD.store(options.threshold, Ordering::Relaxed);
+    EDGE_ITEMS.store(options.edge_items, Ordering::Relaxed);
+}
+
+/// 表示設定を指定してtensorを表示するためのwrapper。`TensorBase::display_with`で作る。
+pub struct DisplayTensor<'a, P, E>
+where
+    P: TensorPointer<Elem = E>,
+{
+    tensor: &'a TensorBase<P, E>,
+    options: PrintOptions,
+}
+
+/// 各軸で表示するindex。`None`は省略した部分を表す。
+fn shown_indices(dim: isize, edge_items: usize, summarize: bool) -> Vec<Option<isize>> {
+    let edge = edge_items as isize;
+    if summarize && dim > 2 * edge {
+        (0..edge)
+            .map(Some)
+            .chain(std::iter::once(None))
+            .chain((dim - edge..dim).map(Some))
+            .collect()
+    } else {
+        (0..dim).map(Some).collect()
+    }
+}
+
+impl<P, E> TensorBase<P, E>
+where
+    P: TensorPointer<Elem = E> + Cpu,
+{
+    /// 表示設定を指定して表示する。
+    pub fn display_with(&self, options: PrintOptions) -> DisplayTensor<'_, P, E> {
+        DisplayTensor {
+            tensor: self,
+            options,
+        }
+    }
+
+    /// 表示する要素を論理的な順番で文字列にする。
+    fn collect_elms<F>(
+        &self,
+        shown: &[Vec<Option<isize>>],
+        axis: usize,
+        offset: isize,
+        fmt_elm: &F,
+        out: &mut Vec<String>,
+    ) where
+        F: Fn(&E) -> String,
+    {
+        if axis == shown.len() {
+            let elm = unsafe { &*self.ptr.as_ptr().offset(offset) };
+            out.push(fmt_elm(elm));
+            return;
+        }
+        for index in shown[axis].iter().flatten() {
+            let offset = offset + index * self.stride[axis];
+            self.collect_elms(shown, axis + 1, offset, fmt_elm, out);
+        }
+    }
+
+    /// shapeに合わせて括弧を入れ子にして書く。`indent`は2行目以降の先頭に入れる空白の数。
+    fn write_nested<F>(&self, out: &mut String, options: PrintOptions, indent: usize, fmt_elm: F)
+    where
+        F: Fn(&E) -> String,
+    {
+        if self.shape.num_elms() == 0 {
+            out.push_str("[]");
+            return;
+        }
+        let summarize = self.shape.num_elms() > options.threshold;
+        let shown = self
+            .shape
+            .iter()
+            .map(|dim| shown_indices(*dim, options.edge_items, summarize))
+            .collect::<Vec<_>>();
+        let mut elms = Vec::new();
+        self.collect_elms(&shown, 0, 0, &fmt_elm, &mut elms);
+        let width = elms.iter().map(|s| s.chars().count()).max().unwrap_or(0);
+        write_axis(out, &shown, 0, indent, width, &mut elms.into_iter());
+    }
+}
+
+fn write_axis(
+    out: &mut String,
+    shown: &[Vec<Option<isize>>],
+    axis: usize,
+    indent: usize,
+    width: usize,
+    elms: &mut impl Iterator<Item = String>,
+) {
+    let num_dim = shown.len();
+    let is_last_axis = axis == num_dim - 1;
+    out.push('[');
+    for (i, index) in shown[axis].iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+            if is_last_axis {
+                out.push(' ');
+            } else {
+                // 高次元の区切りほど空行を増やす
+                out.push_str(&"\n".repeat(num_dim - axis - 1));
+                out.push_str(&" ".repeat(indent + axis + 1));
+            }
+        }
+        match index {
+            None => out.push_str("..."),
+            Some(_) if is_last_axis => {
+                let s = elms.next().unwrap();
+                let pad = width - s.chars().count();
+                out.push_str(&" ".repeat(pad));
+                out.push_str(&s);
+            }
+            Some(_) => write_axis(out, shown, axis + 1, indent, width, elms),
+        }
+    }
+    out.push(']');
+}
+
+impl<'a, P, E> Display for DisplayTensor<'a, P, E>
+where
+    P: TensorPointer<Elem = E> + Cpu,
+    E: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        let precision = f.precision();
+        self.tensor
+            .write_nested(&mut out, self.options, 0, |x| match precision {
+                Some(p) => format!("{:.*}", p, x),
+                None => format!("{}", x),
+            });
+        f.write_str(&out)
+    }
+}
+
+/// NumPyと同じく、shapeに合わせて括弧を入れ子にして表示する。
+/// `{:.3}`のように精度を指定すると、各要素に適用される。
+impl<P, E> Display for TensorBase<P, E>
+where
+    P: TensorPointer<Elem = E> + Cpu,
+    E: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.display_with(print_options()), f)
+    }
+}
+
+/// 要素に加えて、shapeと要素の型も表示する。
+impl<P, E> Debug for TensorBase<P, E>
+where
+    P: TensorPointer<Elem = E> + Cpu,
+    E: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        const PREFIX: &str = "tensor(";
+        let mut out = String::from(PREFIX);
+        let precision = f.precision();
+        self.write_nested(
+            &mut out,
+            print_options(),
+            PREFIX.len(),
+            |x| match precision {
+                Some(p) => format!("{:.*?}", p, x),
+                None => format!("{:?}", x),
+            },
+        );
+        write!(
+            f,
+            "{}, shape={:?}, dtype={})",
+            out,
+            &self.shape[..],
+            std::any::type_name::<E>()
+        )
+    }
+}
+
+impl<E: Copy + Debug> Debug for CpuCowTensor<'_, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CpuCowTensor::View(t) => Debug::fmt(t, f),
+            CpuCowTensor::Owned(t) => Debug::fmt(t, f),
+        }
+    }
+}
+
+#[cfg(test)]
+use crate::shape::Shape;
+#[cfg(test)]
+use crate::tensor::CpuTensor;
+
+#[test]
+fn display_test() {
+    let a = CpuTensor::from_vec(vec![1, 20, 3, 4, 5, 600], Shape::new(vec![2, 3]));
+    assert_eq!(format!("{}", a), "[[  1,  20,   3],\n [  4,   5, 600]]");
+    // strideを考慮する
+    assert_eq!(
+        format!("{}", a.t()),
+        "[[  1,   4],\n [ 20,   5],\n [  3, 600]]"
+    );
+    let b = CpuTensor::from_vec(vec![0.5f32, 1.25], Shape::new(vec![2]));
+    assert_eq!(format!("{:.1}", b), "[0.5, 1.2]");
+}
+
+#[test]
+fn display_3d_test() {
+    let a = CpuTensor::from_vec((0..8).collect(), Shape::new(vec![2, 2, 2]));
+    assert_eq!(
+        format!("{}", a),
+        "[[[0, 1],\n  [2, 3]],\n\n [[4, 5],\n  [6, 7]]]"
+    );
+}
+
+#[test]
+fn debug_test() {
+    let a = CpuTensor::from_vec(vec![1f32, 2., 3., 4.], Shape::new(vec![2, 2]));
+    assert_eq!(
+        format!("{:?}", a.to_view()),
+        "tensor([[1.0, 2.0],\n        [3.0, 4.0]], shape=[2, 2], dtype=f32)"
+    );
+}
+
+#[test]
+fn summarize_test() {
+    let a = CpuTensor::from_vec((0..100).collect(), Shape::new(vec![10, 10]));
+    let options = PrintOptions {
+        threshold: 10,
+        edge_items: 1,
+    };
+    assert_eq!(
+        format!("{}", a.display_with(options)),
+        "[[ 0, ...,  9],\n ...,\n [90, ..., 99]]"
+    );
+    let b = CpuTensor::from_vec((0..10).collect(), Shape::new(vec![10]));
+    assert_eq!(
+        format!("{}", b.display_with(options)),
+        "[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]"
+    );
+}
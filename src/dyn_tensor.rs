@@ -0,0 +1,281 @@
+use std::fmt::{self, Debug, Display, Formatter};
+
+use half::{bf16, f16};
+
+use crate::shape::Shape;
+use crate::tensor::CpuTensor;
+
+/// 実行時に扱う要素の型
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DType {
+    F16,
+    BF16,
+    F32,
+    F64,
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    Bool,
+}
+
+impl DType {
+    /// 1要素のbyte数
+    pub fn size(&self) -> usize {
+        match self {
+            DType::I8 | DType::U8 | DType::Bool => 1,
+            DType::F16 | DType::BF16 | DType::I16 => 2,
+            DType::F32 | DType::I32 => 4,
+            DType::F64 | DType::I64 => 8,
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, DType::F16 | DType::BF16 | DType::F32 | DType::F64)
+    }
+}
+
+impl Display for DType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DType::F16 => "f16",
+            DType::BF16 => "bf16",
+            DType::F32 => "f32",
+            DType::F64 => "f64",
+            DType::I8 => "i8",
+            DType::I16 => "i16",
+            DType::I32 => "i32",
+            DType::I64 => "i64",
+            DType::U8 => "u8",
+            DType::Bool => "bool",
+        };
+        f.write_str(name)
+    }
+}
+
+/// `DynTensor`に入れられる要素の型
+pub trait DynElement: Copy + 'static {
+    const DTYPE: DType;
+
+    fn into_dyn(a: CpuTensor<Self>) -> DynTensor;
+    fn from_dyn(a: DynTensor) -> Result<CpuTensor<Self>, DynTensor>;
+    fn from_dyn_ref(a: &DynTensor) -> Option<&CpuTensor<Self>>;
+    fn from_dyn_mut(a: &mut DynTensor) -> Option<&mut CpuTensor<Self>>;
+}
+
+/// 要素の型が実行時に決まるtensor。ファイルの読み込みなど、型が静的に分からない場合に使う。
+/// 型ごとの処理は`dyn_dispatch!`で書ける。
+pub enum DynTensor {
+    F16(CpuTensor<f16>),
+    BF16(CpuTensor<bf16>),
+    F32(CpuTensor<f32>),
+    F64(CpuTensor<f64>),
+    I8(CpuTensor<i8>),
+    I16(CpuTensor<i16>),
+    I32(CpuTensor<i32>),
+    I64(CpuTensor<i64>),
+    U8(CpuTensor<u8>),
+    Bool(CpuTensor<bool>),
+}
+
+/// `DynTensor`の中身の`CpuTensor`を`$t`に束縛して、全ての型について`$body`を実行する。
+/// `$body`は型ごとに展開されるため、ジェネリックな関数を呼べる。
+///
+/// ```
+/// use bokutotu::dyn_dispatch;
+/// use bokutotu::dyn_tensor::DynTensor;
+/// use bokutotu::shape::Shape;
+/// use bokutotu::tensor::CpuTensor;
+///
+/// let tensor = DynTensor::from(CpuTensor::from_vec(vec![1f32, 2.], Shape::new(vec![2])));
+/// let num_dim = dyn_dispatch!(&tensor, t => t.shape().num_dim());
+/// assert_eq!(num_dim, 1);
+/// ```
+#[macro_export]
+macro_rules! dyn_dispatch {
+    ($tensor:expr, $t:ident => $body:expr) => {
+        match $tensor {
+            $crate::dyn_tensor::DynTensor::F16($t) => $body,
+            $crate::dyn_tensor::DynTensor::BF16($t) => $body,
+            $crate::dyn_tensor::DynTensor::F32($t) => $body,
+            $crate::dyn_tensor::DynTensor::F64($t) => $body,
+            $crate::dyn_tensor::DynTensor::I8($t) => $body,
+            $crate::dyn_tensor::DynTensor::I16($t) => $body,
+            $crate::dyn_tensor::DynTensor::I32($t) => $body,
+            $crate::dyn_tensor::DynTensor::I64($t) => $body,
+            $crate::dyn_tensor::DynTensor::U8($t) => $body,
+            $crate::dyn_tensor::DynTensor::Bool($t) => $body,
+        }
+    };
+}
+
+macro_rules! impl_dyn_element {
+    ( $( ($ty:ty, $variant:ident) ),* ) => {
+        $(
+            impl DynElement for $ty {
+                const DTYPE: DType = DType::$variant;
+
+                #[inline]
+                fn into_dyn(a: CpuTensor<Self>) -> DynTensor {
+                    DynTensor::$variant(a)
+                }
+
+                #[inline]
+                fn from_dyn(a: DynTensor) -> Result<CpuTensor<Self>, DynTensor> {
+                    match a {
+                        DynTensor::$variant(a) => Ok(a),
+                        a => Err(a),
+                    }
+                }
+
+                #[inline]
+                fn from_dyn_ref(a: &DynTensor) -> Option<&CpuTensor<Self>> {
+                    match a {
+                        DynTensor::$variant(a) => Some(a),
+                        _ => None,
+                    }
+                }
+
+                #[inline]
+                fn from_dyn_mut(a: &mut DynTensor) -> Option<&mut CpuTensor<Self>> {
+                    match a {
+                        DynTensor::$variant(a) => Some(a),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_dyn_element!(
+    (f16, F16),
+    (bf16, BF16),
+    (f32, F32),
+    (f64, F64),
+    (i8, I8),
+    (i16, I16),
+    (i32, I32),
+    (i64, I64),
+    (u8, U8),
+    (bool, Bool)
+);
+
+impl<E: DynElement> From<CpuTensor<E>> for DynTensor {
+    fn from(a: CpuTensor<E>) -> Self {
+        E::into_dyn(a)
+    }
+}
+
+impl DynTensor {
+    pub fn dtype(&self) -> DType {
+        match self {
+            DynTensor::F16(_) => DType::F16,
+            DynTensor::BF16(_) => DType::BF16,
+            DynTensor::F32(_) => DType::F32,
+            DynTensor::F64(_) => DType::F64,
+            DynTensor::I8(_) => DType::I8,
+            DynTensor::I16(_) => DType::I16,
+            DynTensor::I32(_) => DType::I32,
+            DynTensor::I64(_) => DType::I64,
+            DynTensor::U8(_) => DType::U8,
+            DynTensor::Bool(_) => DType::Bool,
+        }
+    }
+
+    pub fn shape(&self) -> Shape {
+        dyn_dispatch!(self, t => t.shape())
+    }
+
+    pub fn num_elms(&self) -> usize {
+        dyn_dispatch!(self, t => t.num_elms())
+    }
+
+    /// 要素の型が`E`であれば中身を返す。違う場合は`self`をそのまま`Err`で返す。
+    pub fn downcast<E: DynElement>(self) -> Result<CpuTensor<E>, DynTensor> {
+        E::from_dyn(self)
+    }
+
+    pub fn downcast_ref<E: DynElement>(&self) -> Option<&CpuTensor<E>> {
+        E::from_dyn_ref(self)
+    }
+
+    pub fn downcast_mut<E: DynElement>(&mut self) -> Option<&mut CpuTensor<E>> {
+        E::from_dyn_mut(self)
+    }
+
+    /// 要素を`dtype`に変換したtensorを返す。変換の規則は`Cast`を参照。
+    pub fn cast(&self, dtype: DType) -> DynTensor {
+        dyn_dispatch!(self, t => match dtype {
+            DType::F16 => DynTensor::F16(t.cast()),
+            DType::BF16 => DynTensor::BF16(t.cast()),
+            DType::F32 => DynTensor::F32(t.cast()),
+            DType::F64 => DynTensor::F64(t.cast()),
+            DType::I8 => DynTensor::I8(t.cast()),
+            DType::I16 => DynTensor::I16(t.cast()),
+            DType::I32 => DynTensor::I32(t.cast()),
+            DType::I64 => DynTensor::I64(t.cast()),
+            DType::U8 => DynTensor::U8(t.cast()),
+            DType::Bool => DynTensor::Bool(t.cast()),
+        })
+    }
+}
+
+impl Display for DynTensor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        dyn_dispatch!(self, t => Display::fmt(t, f))
+    }
+}
+
+impl Debug for DynTensor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        dyn_dispatch!(self, t => Debug::fmt(t, f))
+    }
+}
+
+#[test]
+fn dyn_tensor_downcast_test() {
+    let a = CpuTensor::from_vec(vec![1i32, 2, 3], Shape::new(vec![3]));
+    let d = DynTensor::from(a);
+    assert_eq!(d.dtype(), DType::I32);
+    assert_eq!(d.shape(), Shape::new(vec![3]));
+    assert!(d.downcast_ref::<f32>().is_none());
+    assert_eq!(d.downcast_ref::<i32>().unwrap().to_vec(), vec![1, 2, 3]);
+    let d = d.downcast::<i64>().unwrap_err();
+    assert_eq!(d.downcast::<i32>().unwrap().to_vec(), vec![1, 2, 3]);
+}
+
+#[test]
+fn dyn_tensor_cast_test() {
+    let d = DynTensor::from(CpuTensor::from_vec(
+        vec![1.5f64, -2.5, 0.],
+        Shape::new(vec![3]),
+    ));
+    let c = d.cast(DType::I8);
+    assert_eq!(c.dtype(), DType::I8);
+    assert_eq!(c.downcast::<i8>().unwrap().to_vec(), vec![1, -2, 0]);
+    let b = d.cast(DType::Bool);
+    assert_eq!(
+        b.downcast::<bool>().unwrap().to_vec(),
+        vec![true, true, false]
+    );
+    assert_eq!(d.cast(DType::F16).dtype().size(), 2);
+}
+
+#[test]
+fn dyn_dispatch_test() {
+    fn sum_as_f64<E: crate::cast::Cast<f64>>(a: &CpuTensor<E>) -> f64 {
+        a.iter().map(|x| x.cast()).sum()
+    }
+    let ds = [
+        DynTensor::from(CpuTensor::from_vec(vec![1u8, 2], Shape::new(vec![2]))),
+        DynTensor::from(CpuTensor::from_vec(vec![0.5f32, 0.25], Shape::new(vec![2]))),
+        DynTensor::from(CpuTensor::from_vec(vec![true, true], Shape::new(vec![2]))),
+    ];
+    let sums = ds
+        .iter()
+        .map(|d| dyn_dispatch!(d, t => sum_as_f64(t)))
+        .collect::<Vec<_>>();
+    assert_eq!(sums, vec![3., 0.75, 2.]);
+}
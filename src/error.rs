@@ -0,0 +1,72 @@
+use thiserror::Error;
+
+use crate::shape::{BroadcastError, Shape};
+
+/// tensorの生成、shapeの変更、slicingで起こるエラー
+/// panicする関数には、このエラーを返す`try_`付きの関数が用意されている。
+#[derive(Error, Clone, PartialEq, Eq, Debug)]
+pub enum TensorError {
+    #[error("shape mismatch: expected {expected:?}, got {got:?}")]
+    ShapeMismatch { expected: Shape, got: Shape },
+
+    #[error("shape {shape:?} has {expected} elements, but got {got} elements")]
+    NumElmsMismatch {
+        shape: Shape,
+        expected: usize,
+        got: usize,
+    },
+
+    #[error(
+        "invalid shape {shape:?}: shape must have at least one axis and no negative dimension"
+    )]
+    InvalidShape { shape: Shape },
+
+    #[error("index {index} is out of bounds for axis {axis} with size {dim}")]
+    IndexOutOfBounds {
+        axis: usize,
+        index: isize,
+        dim: isize,
+    },
+
+    #[error("slice step cannot be zero (axis {axis})")]
+    ZeroStep { axis: usize },
+
+    #[error("expected {expected} axes, but got {got} axes")]
+    NumDimMismatch { expected: usize, got: usize },
+
+    #[error("axis {axis} is out of bounds for tensor of dimension {num_dim}")]
+    InvalidAxis { axis: usize, num_dim: usize },
+
+    #[error("start axis {start} must not be greater than end axis {end}")]
+    InvalidAxisRange { start: usize, end: usize },
+
+    #[error("axis {axis} is repeated")]
+    DuplicateAxis { axis: usize },
+
+    #[error("an index can only have a single ellipsis")]
+    MultipleEllipsis,
+
+    #[error("cannot squeeze axis {axis} with size {dim}")]
+    SqueezeNonUnitAxis { axis: usize, dim: isize },
+
+    #[error("tensor of shape {shape:?} cannot be viewed as shape {to:?} without copying")]
+    RequiresCopy { shape: Shape, to: Shape },
+
+    #[error("index points a region, not a single element")]
+    NotSingleElement,
+
+    #[error("split sizes {sizes:?} don't sum to the size {dim} of the axis")]
+    InvalidSplitSizes { sizes: Vec<usize>, dim: isize },
+
+    #[error("chunk expects `n` to be greater than 0")]
+    ZeroChunks,
+
+    #[error("expected at least one tensor")]
+    EmptyTensorList,
+
+    #[error("offset {offset} is out of bounds for buffer of length {len}")]
+    OffsetOutOfBounds { offset: usize, len: usize },
+
+    #[error(transparent)]
+    Broadcast(#[from] BroadcastError),
+}
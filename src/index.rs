@@ -1,65 +1,196 @@
-use std::ops::{Deref, Range, RangeFrom, RangeFull, RangeInclusive, RangeTo};
+use std::ops::{Deref, Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
 
+use crate::error::TensorError;
+
+/// 1つの軸に対するindex
+/// 負の値はPythonと同様に末尾から数える。
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Inner {
+pub enum Inner {
+    /// 1つの要素を指す。この軸はslice後のshapeから取り除かれる。
+    Point(isize),
+    /// Pythonの`start:end:step`と同じで、`end`は含まない。
+    /// `None`はstepの符号に応じて先頭または末尾を表す。
+    Range {
+        start: Option<isize>,
+        end: Option<isize>,
+        step: isize,
+    },
+    /// 大きさ1の軸を新しく挿入する。`index!`では`NewAxis`と書く。
+    NewAxis,
+    /// 指定されていない残りの軸を全て`..`で埋める。`index!`では`...`と書く。
+    Ellipsis,
+}
+
+/// 軸の大きさに合わせて解決したindex
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct ResolvedInner {
+    /// 先頭の要素の位置
     pub(crate) start: isize,
-    pub(crate) end: Option<isize>,
+    /// 要素数。`Point`の場合は`None`
+    pub(crate) len: Option<isize>,
     pub(crate) step: isize,
 }
 
 impl Inner {
-    pub fn new(start: isize, end: Option<isize>, step: isize) -> Self {
-        Self { start, end, step }
+    pub fn new(start: Option<isize>, end: Option<isize>, step: isize) -> Self {
+        Inner::Range { start, end, step }
     }
 
+    /// stepを設定する。`Point`の場合は何もしない。
     pub fn set_step(self, step: isize) -> Self {
-        let mut s = self;
-        s.step = step;
-        s
+        match self {
+            Inner::Range { start, end, .. } => Inner::Range { start, end, step },
+            point => point,
+        }
     }
 
     fn is_point_single_elm(&self) -> bool {
-        self.end.is_none()
+        matches!(self, Inner::Point(_))
     }
 
-    pub(crate) fn is_range(&self) -> bool {
-        self.end.is_none()
+    /// 大きさ`dim`の軸`axis`に対して、NumPyと同じ規則でindexを解決する。
+    /// `Point`が範囲外の場合やstepが0の場合はエラーを返す。rangeの範囲外の部分は切り詰める。
+    /// `NewAxis`は元のtensorの軸に対応しないため、`dim`に関わらず大きさ1になる。
+    pub(crate) fn resolve(&self, axis: usize, dim: isize) -> Result<ResolvedInner, TensorError> {
+        match *self {
+            Inner::NewAxis => Ok(ResolvedInner {
+                start: 0,
+                len: Some(1),
+                step: 1,
+            }),
+            Inner::Ellipsis => unreachable!("ellipsis must be expanded before resolving"),
+            Inner::Point(index) => {
+                let start = if index < 0 { index + dim } else { index };
+                if start < 0 || dim <= start {
+                    return Err(TensorError::IndexOutOfBounds { axis, index, dim });
+                }
+                Ok(ResolvedInner {
+                    start,
+                    len: None,
+                    step: 1,
+                })
+            }
+            Inner::Range { start, end, step } => {
+                if step == 0 {
+                    return Err(TensorError::ZeroStep { axis });
+                }
+                let (lower, upper) = if step > 0 { (0, dim) } else { (-1, dim - 1) };
+                let clamp = |x: Option<isize>, default: isize| match x {
+                    None => default,
+                    Some(x) if x < 0 => (x + dim).max(lower),
+                    Some(x) => x.min(upper),
+                };
+                let (start, end) = if step > 0 {
+                    (clamp(start, lower), clamp(end, upper))
+                } else {
+                    (clamp(start, upper), clamp(end, lower))
+                };
+                let len = if step > 0 && start < end {
+                    (end - start - 1) / step + 1
+                } else if step < 0 && end < start {
+                    (start - end - 1) / -step + 1
+                } else {
+                    0
+                };
+                Ok(ResolvedInner {
+                    start,
+                    len: Some(len),
+                    step,
+                })
+            }
+        }
     }
 }
 
 impl From<Range<isize>> for Inner {
     fn from(range: Range<isize>) -> Self {
-        Inner::new(range.start, Some(range.end - 1), 1)
+        Inner::new(Some(range.start), Some(range.end), 1)
     }
 }
 
 impl From<RangeFull> for Inner {
     fn from(_: RangeFull) -> Self {
-        Inner::new(0, Some(-1), 1)
+        Inner::new(None, None, 1)
     }
 }
 
 impl From<RangeTo<isize>> for Inner {
     fn from(range: RangeTo<isize>) -> Self {
-        Inner::new(0, Some(range.end - 1), 1)
+        Inner::new(None, Some(range.end), 1)
     }
 }
 
 impl From<RangeFrom<isize>> for Inner {
     fn from(range: RangeFrom<isize>) -> Self {
-        Inner::new(range.start, None, 1)
+        Inner::new(Some(range.start), None, 1)
+    }
+}
+
+/// 終端を含む範囲の終端を、含まない終端に直す。
+/// stepが正の場合は終端+1、負の場合は終端-1にする。
+/// 結果が-1になる場合は末尾から数えた位置と区別できないため、最後の要素まで(負のstepでは先頭まで)を表す`None`にする。
+fn inclusive_end(end: isize, step: isize) -> Option<isize> {
+    match (end, step > 0) {
+        (-1, true) | (0, false) => None,
+        (end, true) => Some(end + 1),
+        (end, false) => Some(end - 1),
     }
 }
 
 impl From<RangeInclusive<isize>> for Inner {
     fn from(range: RangeInclusive<isize>) -> Inner {
-        Inner::new(*range.start(), Some(*range.end()), 1)
+        range.with_step(1)
+    }
+}
+
+impl From<RangeToInclusive<isize>> for Inner {
+    fn from(range: RangeToInclusive<isize>) -> Inner {
+        range.with_step(1)
+    }
+}
+
+/// stepを指定して`Inner`に変換する。`index!`で`range;step`と書いた場合に使う。
+/// 終端を含む範囲はstepの符号によって終端の扱いが変わるため、`Inner::from`の後に`set_step`するのではなくこれを使う。
+pub trait WithStep {
+    fn with_step(self, step: isize) -> Inner;
+}
+
+macro_rules! impl_with_step {
+    ( $( $ty:ty ),* ) => {
+        $(
+            impl WithStep for $ty {
+                fn with_step(self, step: isize) -> Inner {
+                    Inner::from(self).set_step(step)
+                }
+            }
+        )*
+    };
+}
+
+impl_with_step!(
+    Inner,
+    isize,
+    Range<isize>,
+    RangeFull,
+    RangeTo<isize>,
+    RangeFrom<isize>
+);
+
+impl WithStep for RangeInclusive<isize> {
+    fn with_step(self, step: isize) -> Inner {
+        Inner::new(Some(*self.start()), inclusive_end(*self.end(), step), step)
+    }
+}
+
+impl WithStep for RangeToInclusive<isize> {
+    fn with_step(self, step: isize) -> Inner {
+        Inner::new(None, inclusive_end(self.end, step), step)
     }
 }
 
 impl From<isize> for Inner {
     fn from(index: isize) -> Self {
-        Inner::new(index, None, 1)
+        Inner::Point(index)
     }
 }
 
@@ -77,9 +208,35 @@ impl TensorIndex {
     }
 
     pub(crate) fn is_point_single_elm(&self) -> bool {
-        self.iter()
-            .map(|item| item.is_point_single_elm())
-            .any(|x| x)
+        self.iter().all(|item| item.is_point_single_elm())
+    }
+
+    /// `Ellipsis`を足りない数の`..`に置き換える。
+    /// 結果の`NewAxis`以外の要素の数は`num_dim`と等しくなる。
+    pub(crate) fn expand_ellipsis(&self, num_dim: usize) -> Result<Vec<Inner>, TensorError> {
+        let num_ellipsis = self.iter().filter(|x| **x == Inner::Ellipsis).count();
+        if num_ellipsis > 1 {
+            return Err(TensorError::MultipleEllipsis);
+        }
+        let num_index = self
+            .iter()
+            .filter(|x| !matches!(x, Inner::NewAxis | Inner::Ellipsis))
+            .count();
+        if num_index > num_dim || (num_ellipsis == 0 && num_index != num_dim) {
+            return Err(TensorError::NumDimMismatch {
+                expected: num_dim,
+                got: num_index,
+            });
+        }
+        let mut res = Vec::with_capacity(self.len() + num_dim - num_index);
+        for idx in self.iter() {
+            if *idx == Inner::Ellipsis {
+                res.extend((num_index..num_dim).map(|_| Inner::from(..)));
+            } else {
+                res.push(idx.clone());
+            }
+        }
+        Ok(res)
     }
 }
 
@@ -98,6 +255,22 @@ impl Deref for TensorIndex {
 
 #[macro_export]
 macro_rules! index {
+    (@parse [$($stack:tt)*] ..., $($t:tt)*) => {
+        index!(@parse [$($stack)* $crate::index::Inner::Ellipsis, ] $($t)*)
+    };
+
+    (@parse [$($stack:tt)*] ...) => {
+        index!(@parse [$($stack)*] $crate::index::Inner::Ellipsis)
+    };
+
+    (@parse [$($stack:tt)*] NewAxis, $($t:tt)*) => {
+        index!(@parse [$($stack)* $crate::index::Inner::NewAxis, ] $($t)*)
+    };
+
+    (@parse [$($stack:tt)*] NewAxis) => {
+        index!(@parse [$($stack)*] $crate::index::Inner::NewAxis)
+    };
+
     (@parse [$($stack:tt)*] $range:expr;$step:expr) => {
             $crate::index::TensorIndex::from(
                 vec![
@@ -145,7 +318,7 @@ macro_rules! index {
     };
 
     (@convert $range:expr, $step:expr) => {
-        $crate::index::Inner::from($range).set_step($step)
+        $crate::index::WithStep::with_step($range, $step)
     };
 
     ($($t:tt)*) => {
@@ -162,18 +335,21 @@ fn index_test_null() {
 #[test]
 fn index_test_int() {
     let index = index![1];
-    assert_eq!(index, TensorIndex::from(vec![Inner::new(1, None, 1)]));
+    assert_eq!(index, TensorIndex::from(vec![Inner::Point(1)]));
 }
 #[test]
 fn index_test_1d() {
     let inner = index![1..2];
-    assert_eq!(inner, TensorIndex::from(vec![Inner::new(1, Some(1), 1)]));
+    assert_eq!(
+        inner,
+        TensorIndex::from(vec![Inner::new(Some(1), Some(2), 1)])
+    );
 }
 
 #[test]
 fn index_test_1d_rangefull() {
     let index = index![..];
-    let ans = Inner::new(0, Some(-1), 1);
+    let ans = Inner::new(None, None, 1);
     let ans = TensorIndex::from(vec![ans]);
     assert_eq!(index, ans)
 }
@@ -181,7 +357,7 @@ fn index_test_1d_rangefull() {
 #[test]
 fn index_test_1d_rengaefull_step() {
     let index = index![..;2];
-    let ans_inner = Inner::new(0, Some(-1), 2);
+    let ans_inner = Inner::new(None, None, 2);
     let ans = TensorIndex::from(vec![ans_inner]);
     assert_eq!(ans, index);
 }
@@ -189,19 +365,28 @@ fn index_test_1d_rengaefull_step() {
 #[test]
 fn index_test_1d_eq() {
     let index = index![1..=4];
-    assert_eq!(index, TensorIndex::from(vec![Inner::new(1, Some(4), 1)]));
+    assert_eq!(
+        index,
+        TensorIndex::from(vec![Inner::new(Some(1), Some(5), 1)])
+    );
 }
 
 #[test]
 fn index_test_full() {
     let index = index![1..10;2];
-    assert_eq!(index, TensorIndex::from(vec![Inner::new(1, Some(9), 2)]))
+    assert_eq!(
+        index,
+        TensorIndex::from(vec![Inner::new(Some(1), Some(10), 2)])
+    )
 }
 
 #[test]
 fn index_test_2d() {
     let index = index![1..2, ..3];
-    let v = vec![Inner::new(1, Some(1), 1), Inner::new(0, Some(2), 1)];
+    let v = vec![
+        Inner::new(Some(1), Some(2), 1),
+        Inner::new(None, Some(3), 1),
+    ];
     let ans = TensorIndex::from(v);
     assert_eq!(ans, index);
 }
@@ -209,7 +394,7 @@ fn index_test_2d() {
 #[test]
 fn index_test_2d_fullrange_with_stride() {
     let index = index![..;2, ..;2];
-    let v = vec![Inner::new(0, Some(-1), 2), Inner::new(0, Some(-1), 2)];
+    let v = vec![Inner::new(None, None, 2), Inner::new(None, None, 2)];
     let ans = TensorIndex::from(v);
     assert_eq!(ans, index);
 }
@@ -218,10 +403,159 @@ fn index_test_2d_fullrange_with_stride() {
 fn index_test_3d() {
     let index = index![1..2, ..3, ..];
     let v = vec![
-        Inner::new(1, Some(1), 1),
-        Inner::new(0, Some(2), 1),
-        Inner::new(0, Some(-1), 1),
+        Inner::new(Some(1), Some(2), 1),
+        Inner::new(None, Some(3), 1),
+        Inner::new(None, None, 1),
     ];
     let ans = TensorIndex::from(v);
     assert_eq!(ans, index);
 }
+
+#[test]
+// 負のstepでは逆順の範囲を書く
+#[allow(clippy::reversed_empty_ranges)]
+fn index_test_inclusive_step() {
+    let index = index![5..=0;-1, ..=2;-1, 1..=-1;2, ..=-3;-1];
+    let v = vec![
+        Inner::new(Some(5), None, -1),
+        Inner::new(None, Some(1), -1),
+        Inner::new(Some(1), None, 2),
+        Inner::new(None, Some(-4), -1),
+    ];
+    assert_eq!(index, TensorIndex::from(v));
+}
+
+#[test]
+fn index_test_negative() {
+    let index = index![-1, ..-2, -3..;-1];
+    let v = vec![
+        Inner::Point(-1),
+        Inner::new(None, Some(-2), 1),
+        Inner::new(Some(-3), None, -1),
+    ];
+    assert_eq!(index, TensorIndex::from(v));
+}
+
+macro_rules! impl_resolve_test {
+    ($fn_name:ident, $inner:expr, $dim:expr, $start:expr, $len:expr, $step:expr) => {
+        #[test]
+        fn $fn_name() {
+            let resolved = Inner::from($inner).resolve(0, $dim).unwrap();
+            let ans = ResolvedInner {
+                start: $start,
+                len: $len,
+                step: $step,
+            };
+            assert_eq!(resolved, ans);
+        }
+    };
+}
+
+impl_resolve_test!(resolve_point_negative, -1, 5, 4, None, 1);
+impl_resolve_test!(resolve_range_to_negative, ..-2, 5, 0, Some(3), 1);
+impl_resolve_test!(resolve_range_from_negative, -2.., 5, 3, Some(2), 1);
+impl_resolve_test!(
+    resolve_reverse,
+    Inner::from(..).set_step(-1),
+    5,
+    4,
+    Some(5),
+    -1
+);
+impl_resolve_test!(
+    resolve_reverse_step_2,
+    Inner::from(..).set_step(-2),
+    5,
+    4,
+    Some(3),
+    -2
+);
+impl_resolve_test!(
+    resolve_reverse_from,
+    Inner::new(Some(3), Some(0), -1),
+    5,
+    3,
+    Some(3),
+    -1
+);
+impl_resolve_test!(
+    resolve_reverse_inclusive,
+    RangeInclusive::new(5, 0).with_step(-1),
+    6,
+    5,
+    Some(6),
+    -1
+);
+impl_resolve_test!(
+    resolve_reverse_inclusive_step_2,
+    (..=1).with_step(-2),
+    6,
+    5,
+    Some(3),
+    -2
+);
+impl_resolve_test!(resolve_range_clamp, -10..10, 5, 0, Some(5), 1);
+impl_resolve_test!(
+    resolve_range_empty,
+    Inner::new(Some(3), Some(1), 1),
+    5,
+    3,
+    Some(0),
+    1
+);
+
+#[test]
+fn resolve_error() {
+    assert_eq!(
+        Inner::Point(-6).resolve(1, 5),
+        Err(TensorError::IndexOutOfBounds {
+            axis: 1,
+            index: -6,
+            dim: 5
+        })
+    );
+    assert_eq!(
+        Inner::from(..).set_step(0).resolve(0, 5),
+        Err(TensorError::ZeroStep { axis: 0 })
+    );
+}
+
+#[test]
+fn index_test_ellipsis_new_axis() {
+    let index = index![..., 0];
+    assert_eq!(
+        index,
+        TensorIndex::from(vec![Inner::Ellipsis, Inner::Point(0)])
+    );
+    let index = index![NewAxis, .., 1..3;2, ...];
+    let v = vec![
+        Inner::NewAxis,
+        Inner::new(None, None, 1),
+        Inner::new(Some(1), Some(3), 2),
+        Inner::Ellipsis,
+    ];
+    assert_eq!(index, TensorIndex::from(v));
+}
+
+#[test]
+fn expand_ellipsis_test() {
+    let index = index![NewAxis, ..., 0];
+    let v = vec![
+        Inner::NewAxis,
+        Inner::new(None, None, 1),
+        Inner::new(None, None, 1),
+        Inner::Point(0),
+    ];
+    assert_eq!(index.expand_ellipsis(3).unwrap(), v);
+    assert_eq!(
+        index![..., 0, ...].expand_ellipsis(3),
+        Err(TensorError::MultipleEllipsis)
+    );
+    assert_eq!(
+        index![0, 0, ...].expand_ellipsis(1),
+        Err(TensorError::NumDimMismatch {
+            expected: 1,
+            got: 2
+        })
+    );
+}
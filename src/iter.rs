@@ -0,0 +1,304 @@
+use std::iter::{ExactSizeIterator, FusedIterator, Iterator};
+use std::marker::PhantomData;
+
+use crate::pointer_traits::{Cpu, CpuMut, TensorPointer};
+use crate::shape::{broadcast_update_stride, BroadcastError, Shape, Stride};
+use crate::tensor::TensorBase;
+
+/// shapeとstrideに従って多次元indexを辿り、先頭からのoffsetを返す。
+/// indexのバッファは生成時に一度だけ確保し、要素毎のメモリ確保は行わない。
+#[derive(Clone, Debug)]
+struct StridedIndex {
+    shape: Shape,
+    stride: Stride,
+    index: Vec<isize>,
+    offset: isize,
+    remaining: usize,
+}
+
+impl StridedIndex {
+    fn new(shape: Shape, stride: Stride) -> Self {
+        let index = vec![0; shape.num_dim()];
+        let remaining = shape.num_elms();
+        Self {
+            shape,
+            stride,
+            index,
+            offset: 0,
+            remaining,
+        }
+    }
+
+    /// 現在のindexを1つ進める。最後の軸から順に繰り上げていく。
+    #[inline]
+    fn step(&mut self) {
+        for axis in (0..self.shape.num_dim()).rev() {
+            self.index[axis] += 1;
+            self.offset += self.stride[axis];
+            if self.index[axis] < self.shape[axis] {
+                return;
+            }
+            self.offset -= self.stride[axis] * self.index[axis];
+            self.index[axis] = 0;
+        }
+    }
+
+    #[inline]
+    fn next_offset(&mut self) -> Option<isize> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let offset = self.offset;
+        self.remaining -= 1;
+        if self.remaining != 0 {
+            self.step();
+        }
+        Some(offset)
+    }
+}
+
+/// shape, strideで表される要素の先頭からのoffsetを、論理的な順番(row major)で返すiterator
+pub(crate) struct StridedOffsets(StridedIndex);
+
+impl StridedOffsets {
+    pub(crate) fn new(shape: Shape, stride: Stride) -> Self {
+        StridedOffsets(StridedIndex::new(shape, stride))
+    }
+}
+
+impl Iterator for StridedOffsets {
+    type Item = isize;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_offset()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.remaining, Some(self.0.remaining))
+    }
+}
+
+impl ExactSizeIterator for StridedOffsets {}
+
+/// tensorの要素を論理的な順番(row major)で参照するiterator
+pub struct Iter<'a, E> {
+    ptr: *const E,
+    index: StridedIndex,
+    _marker: PhantomData<&'a E>,
+}
+
+impl<'a, E> Iterator for Iter<'a, E> {
+    type Item = &'a E;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.index.next_offset()?;
+        unsafe { Some(&*self.ptr.offset(offset)) }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.index.remaining, Some(self.index.remaining))
+    }
+}
+
+impl<'a, E> ExactSizeIterator for Iter<'a, E> {}
+impl<'a, E> FusedIterator for Iter<'a, E> {}
+
+/// tensorの要素を論理的な順番(row major)で可変参照するiterator
+pub struct IterMut<'a, E> {
+    ptr: *mut E,
+    index: StridedIndex,
+    _marker: PhantomData<&'a mut E>,
+}
+
+impl<'a, E> Iterator for IterMut<'a, E> {
+    type Item = &'a mut E;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.index.next_offset()?;
+        unsafe { Some(&mut *self.ptr.offset(offset)) }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.index.remaining, Some(self.index.remaining))
+    }
+}
+
+impl<'a, E> ExactSizeIterator for IterMut<'a, E> {}
+impl<'a, E> FusedIterator for IterMut<'a, E> {}
+
+/// 要素と一緒にその多次元indexを返すiterator
+/// indexは要素毎に`Vec`として返されるため、indexが不要な場合は`Iter`を使うこと。
+pub struct IndexedIter<'a, E> {
+    ptr: *const E,
+    index: StridedIndex,
+    _marker: PhantomData<&'a E>,
+}
+
+impl<'a, E> Iterator for IndexedIter<'a, E> {
+    type Item = (Vec<isize>, &'a E);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.index.index.clone();
+        let offset = self.index.next_offset()?;
+        unsafe { Some((index, &*self.ptr.offset(offset))) }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.index.remaining, Some(self.index.remaining))
+    }
+}
+
+impl<'a, E> ExactSizeIterator for IndexedIter<'a, E> {}
+impl<'a, E> FusedIterator for IndexedIter<'a, E> {}
+
+impl<P, E> TensorBase<P, E>
+where
+    P: TensorPointer<Elem = E> + Cpu,
+{
+    /// strideを考慮して、論理的な順番(row major)で要素を参照するiteratorを返す。
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, E> {
+        Iter {
+            ptr: self.ptr.as_ptr(),
+            index: StridedIndex::new(self.shape.clone(), self.stride.clone()),
+            _marker: PhantomData,
+        }
+    }
+
+    /// shapeへbroadcastした時の要素を、論理的な順番(row major)で参照するiteratorを返す。
+    /// broadcastで伸ばされる軸ではstrideが0になるため、同じ要素を繰り返し参照する。
+    #[inline]
+    pub fn broadcast_iter(&self, shape: &Shape) -> Result<Iter<'_, E>, BroadcastError> {
+        let stride = broadcast_update_stride(&self.shape, &self.stride, shape)?;
+        Ok(Iter {
+            ptr: self.ptr.as_ptr(),
+            index: StridedIndex::new(shape.clone(), stride),
+            _marker: PhantomData,
+        })
+    }
+
+    /// 与えたshape, strideで要素を辿るiteratorを返す。
+    /// shape, strideは軸の並べ替えなど、このtensorと同じ要素だけを指すものでなければならない。
+    #[inline]
+    pub(crate) fn strided_iter(&self, shape: Shape, stride: Stride) -> Iter<'_, E> {
+        Iter {
+            ptr: self.ptr.as_ptr(),
+            index: StridedIndex::new(shape, stride),
+            _marker: PhantomData,
+        }
+    }
+
+    /// `iter`と同じ順番で、要素とその多次元indexを返すiteratorを返す。
+    #[inline]
+    pub fn indexed_iter(&self) -> IndexedIter<'_, E> {
+        IndexedIter {
+            ptr: self.ptr.as_ptr(),
+            index: StridedIndex::new(self.shape.clone(), self.stride.clone()),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<P, E> TensorBase<P, E>
+where
+    P: TensorPointer<Elem = E> + CpuMut,
+{
+    /// strideを考慮して、論理的な順番(row major)で要素を可変参照するiteratorを返す。
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, E> {
+        IterMut {
+            ptr: self.ptr.to_slice_mut().as_mut_ptr(),
+            index: StridedIndex::new(self.shape.clone(), self.stride.clone()),
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[test]
+fn iter_contiguous() {
+    use crate::tensor::CpuTensor;
+    let a = CpuTensor::from_vec((0..6).collect(), Shape::new(vec![2, 3]));
+    let iter = a.iter();
+    assert_eq!(iter.len(), 6);
+    assert_eq!(iter.copied().collect::<Vec<i32>>(), vec![0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn iter_strided_view() {
+    use crate::index;
+    use crate::tensor::CpuTensor;
+    let a = CpuTensor::from_vec((0..125).collect(), Shape::new(vec![5, 5, 5]));
+    let v = a.slice(index![.., 2, 1..4;2]);
+    let res = v.iter().copied().collect::<Vec<i32>>();
+    assert_eq!(res, vec![11, 13, 36, 38, 61, 63, 86, 88, 111, 113]);
+}
+
+#[test]
+fn iter_swapped_axis() {
+    use crate::tensor::CpuTensor;
+    let mut a = CpuTensor::from_vec((0..8).collect(), Shape::new(vec![2, 2, 2]));
+    a.swap_axis(0, 2);
+    let res = a.iter().copied().collect::<Vec<i32>>();
+    assert_eq!(res, vec![0, 4, 2, 6, 1, 5, 3, 7]);
+}
+
+#[test]
+fn iter_size_hint() {
+    use crate::tensor::CpuTensor;
+    let a = CpuTensor::from_vec((0..6).collect::<Vec<i32>>(), Shape::new(vec![3, 2]));
+    let mut iter = a.iter();
+    iter.next();
+    iter.next();
+    assert_eq!(iter.len(), 4);
+    assert_eq!(iter.count(), 4);
+}
+
+#[test]
+fn broadcast_iter_test() {
+    use crate::tensor::CpuTensor;
+    let a = CpuTensor::from_vec(vec![1, 2], Shape::new(vec![2, 1]));
+    let res = a
+        .broadcast_iter(&Shape::new(vec![2, 3]))
+        .unwrap()
+        .copied()
+        .collect::<Vec<i32>>();
+    assert_eq!(res, vec![1, 1, 1, 2, 2, 2]);
+}
+
+#[test]
+fn iter_mut_view_mut() {
+    use crate::index;
+    use crate::tensor::CpuTensor;
+    let mut a = CpuTensor::from_vec((0..6).collect(), Shape::new(vec![2, 3]));
+    {
+        let mut v = a.slice_mut(index![.., 1..3]);
+        for x in v.iter_mut() {
+            *x *= 10;
+        }
+    }
+    assert_eq!(a.to_vec(), vec![0, 10, 20, 3, 40, 50]);
+}
+
+#[test]
+fn indexed_iter_test() {
+    use crate::tensor::CpuTensor;
+    let a = CpuTensor::from_vec(vec![10, 20, 30, 40], Shape::new(vec![2, 2]));
+    let res = a.indexed_iter().collect::<Vec<(Vec<isize>, &i32)>>();
+    assert_eq!(
+        res,
+        vec![
+            (vec![0, 0], &10),
+            (vec![0, 1], &20),
+            (vec![1, 0], &30),
+            (vec![1, 1], &40)
+        ]
+    );
+}
@@ -1,11 +1,27 @@
 extern crate cblas;
 extern crate openblas_src;
 
+pub mod add;
 pub mod blas;
+pub mod cast;
+pub mod concat;
+pub mod constructors;
+pub mod display;
+pub mod dyn_tensor;
+pub mod error;
 pub mod graph;
 pub mod index;
+pub mod iter;
+pub mod memory_pool;
+pub mod mmap;
+pub mod ndarray_interop;
 pub mod node;
+pub mod npy;
 pub mod owned_methods;
+pub mod random;
+pub mod reduce;
+pub mod safetensors;
+pub mod select;
 pub mod shape;
 pub mod tensor;
 pub mod tensor_methods;
@@ -0,0 +1,197 @@
+//! `OwnedCpu`が確保するメモリのキャッシュ。
+//!
+//! 有効にすると、解放されたメモリをサイズごとのbucketに残しておき、次に同じbucketのサイズを
+//! 確保する時に再利用する。bucketのサイズは、1MiB未満では2の累乗のbyte数に、
+//! 1MiB以上では1MiBの倍数に切り上げる。デフォルトでは無効になっている。
+use std::alloc::{dealloc, Layout};
+use std::collections::BTreeMap;
+use std::mem::{align_of, size_of, ManuallyDrop};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+/// bucketの最小のbyte数
+const MIN_BUCKET_BYTES: usize = 64;
+/// これ以上のbyte数は2の累乗ではなく、この倍数に切り上げる。大きなtensorで確保する量が倍近くになるのを防ぐ。
+const LARGE_BUCKET_BYTES: usize = 1 << 20;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static POOL: Mutex<Pool> = Mutex::new(Pool::new());
+
+/// メモリの使用状況。全てbyte数。
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct MemoryStats {
+    /// キャッシュが有効な間に確保され、まだtensorが使っているメモリ
+    pub bytes_in_use: usize,
+    /// 解放されてキャッシュに残っているメモリ
+    pub bytes_cached: usize,
+    /// `bytes_in_use`の最大値
+    pub peak_bytes_in_use: usize,
+}
+
+struct Pool {
+    /// (byte数, align)ごとの確保済みのメモリの先頭アドレス
+    blocks: BTreeMap<(usize, usize), Vec<usize>>,
+    stats: MemoryStats,
+}
+
+impl Pool {
+    const fn new() -> Self {
+        Self {
+            blocks: BTreeMap::new(),
+            stats: MemoryStats {
+                bytes_in_use: 0,
+                bytes_cached: 0,
+                peak_bytes_in_use: 0,
+            },
+        }
+    }
+}
+
+fn lock() -> MutexGuard<'static, Pool> {
+    // 中身は数値だけなので、panicしたthreadがあってもそのまま使える
+    POOL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn bucket_bytes(bytes: usize) -> usize {
+    if bytes >= LARGE_BUCKET_BYTES {
+        bytes.div_ceil(LARGE_BUCKET_BYTES) * LARGE_BUCKET_BYTES
+    } else {
+        bytes.max(MIN_BUCKET_BYTES).next_power_of_two()
+    }
+}
+
+// `usize::is_multiple_of`はRust 1.87からなので使わない
+#[allow(clippy::manual_is_multiple_of)]
+fn is_bucket(bytes: usize) -> bool {
+    if bytes >= LARGE_BUCKET_BYTES {
+        bytes % LARGE_BUCKET_BYTES == 0
+    } else {
+        bytes >= MIN_BUCKET_BYTES && bytes.is_power_of_two()
+    }
+}
+
+/// キャッシュを使うかどうかを設定する。無効にしてもキャッシュ済みのメモリは`empty_cache`まで残る。
+pub fn set_caching_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_caching_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// 現在のメモリの使用状況を返す。
+pub fn memory_stats() -> MemoryStats {
+    lock().stats
+}
+
+/// `peak_bytes_in_use`を現在の`bytes_in_use`に戻す。
+pub fn reset_peak_stats() {
+    let mut pool = lock();
+    pool.stats.peak_bytes_in_use = pool.stats.bytes_in_use;
+}
+
+/// キャッシュに残っているメモリを全て解放する。
+pub fn empty_cache() {
+    let blocks = {
+        let mut pool = lock();
+        pool.stats.bytes_cached = 0;
+        std::mem::take(&mut pool.blocks)
+    };
+    for ((bytes, align), addrs) in blocks {
+        // Vec<E>が確保したメモリのlayoutは(capacity * size_of::<E>(), align_of::<E>())
+        let layout = Layout::from_size_align(bytes, align).unwrap();
+        for addr in addrs {
+            unsafe { dealloc(addr as *mut u8, layout) };
+        }
+    }
+}
+
+/// 要素数`len`以上のcapacityを持つ空のVecを返す。キャッシュが有効ならキャッシュから取り出す。
+pub(crate) fn alloc_vec<E>(len: usize) -> Vec<E> {
+    let size = size_of::<E>();
+    if !is_caching_enabled() || size == 0 || len == 0 {
+        return Vec::with_capacity(len);
+    }
+    let bytes = match len.checked_mul(size) {
+        Some(bytes) if bytes <= isize::MAX as usize / 2 => bucket_bytes(bytes),
+        _ => return Vec::with_capacity(len),
+    };
+    if bytes % size != 0 {
+        return Vec::with_capacity(len);
+    }
+    let cached = {
+        let mut pool = lock();
+        let addr = pool
+            .blocks
+            .get_mut(&(bytes, align_of::<E>()))
+            .and_then(|addrs| addrs.pop());
+        if addr.is_some() {
+            pool.stats.bytes_cached -= bytes;
+        }
+        addr
+    };
+    match cached {
+        // 同じbyte数とalignで確保されたメモリなので、Vec<E>として使える
+        Some(addr) => unsafe { Vec::from_raw_parts(addr as *mut E, 0, bytes / size) },
+        None => Vec::with_capacity(bytes / size),
+    }
+}
+
+/// iterの要素を`alloc_vec`で確保したVecに集める。
+pub(crate) fn collect_vec<E, I>(iter: I) -> Vec<E>
+where
+    I: ExactSizeIterator<Item = E>,
+{
+    let mut v = alloc_vec(iter.len());
+    v.extend(iter);
+    v
+}
+
+/// `OwnedCpu`がVecを受け取った時に呼ぶ。キャッシュが有効で記録した場合はtrueを返す。
+pub(crate) fn register<E>(vec: &Vec<E>) -> bool {
+    let bytes = vec.capacity() * size_of::<E>();
+    if !is_caching_enabled() || bytes == 0 {
+        return false;
+    }
+    let mut pool = lock();
+    pool.stats.bytes_in_use += bytes;
+    pool.stats.peak_bytes_in_use = pool.stats.peak_bytes_in_use.max(pool.stats.bytes_in_use);
+    true
+}
+
+/// `register`で記録したVecを`OwnedCpu`が手放す時に呼ぶ。
+pub(crate) fn unregister<E>(vec: &Vec<E>) {
+    let bytes = vec.capacity() * size_of::<E>();
+    let mut pool = lock();
+    pool.stats.bytes_in_use -= bytes;
+}
+
+/// Vecを解放する。キャッシュが有効でbucketのサイズと一致する場合はキャッシュに残す。
+pub(crate) fn release<E>(mut vec: Vec<E>) {
+    let bytes = vec.capacity() * size_of::<E>();
+    if !is_caching_enabled() || !is_bucket(bytes) {
+        return;
+    }
+    vec.clear();
+    let vec = ManuallyDrop::new(vec);
+    let mut pool = lock();
+    pool.blocks
+        .entry((bytes, align_of::<E>()))
+        .or_default()
+        .push(vec.as_ptr() as usize);
+    pool.stats.bytes_cached += bytes;
+}
+
+#[test]
+fn memory_pool_bucket_test() {
+    assert_eq!(bucket_bytes(1), MIN_BUCKET_BYTES);
+    assert_eq!(bucket_bytes(100), 128);
+    assert_eq!(bucket_bytes(128), 128);
+    assert!(is_bucket(256));
+    assert!(!is_bucket(24));
+    // 1MiB以上は1MiBの倍数に切り上げる
+    assert_eq!(bucket_bytes(LARGE_BUCKET_BYTES), LARGE_BUCKET_BYTES);
+    assert_eq!(bucket_bytes(600 << 20 | 1), 601 << 20);
+    assert!(is_bucket(3 << 20));
+    assert!(!is_bucket((3 << 20) + 64));
+}
@@ -0,0 +1,274 @@
+use std::fs::File;
+use std::path::Path;
+
+use half::{bf16, f16};
+use memmap2::MmapOptions;
+use num_complex::{Complex32, Complex64};
+use thiserror::Error;
+
+use crate::error::TensorError;
+use crate::index::TensorIndex;
+use crate::pointer_cpu::MmapCpu;
+use crate::pointer_traits::{TensorPointer, View};
+use crate::shape::{try_slice_update_offset, try_slice_update_shape_stride, Shape};
+use crate::tensor::{CpuMmapTensor, CpuViewMutTensor, CpuViewTensor, TensorBase};
+
+/// ファイルをメモリマップする時の書き込みの扱い
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MmapMode {
+    /// 書き込めない
+    ReadOnly,
+    /// 書き込んだページだけをメモリにコピーする。ファイルは変更されない。
+    CopyOnWrite,
+}
+
+/// メモリマップで起こるエラー
+#[derive(Error, Debug)]
+pub enum MmapError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Tensor(#[from] TensorError),
+
+    #[error("region {offset}..{end} is out of bounds for file of {file_len} bytes")]
+    OutOfRange {
+        offset: u64,
+        end: u64,
+        file_len: u64,
+    },
+
+    #[error("offset {offset} is not aligned to {align} bytes")]
+    Misaligned { offset: u64, align: usize },
+
+    #[error("tensor is mapped as read-only")]
+    ReadOnly,
+}
+
+/// メモリマップしたファイルから直接読める要素の型。
+/// ファイルの中身はこの環境のendianでそのまま解釈する。
+///
+/// # Safety
+/// どのbit列も有効な値になる型だけに実装しなければならない。
+pub unsafe trait MmapElement: Copy {}
+
+macro_rules! impl_mmap_element {
+    ( $( $ty:ty ),* ) => {
+        $(
+            unsafe impl MmapElement for $ty {}
+        )*
+    };
+}
+
+// boolは0と1以外のbit列が不正な値になるため実装しない
+impl_mmap_element!(f32, f64, i8, i16, i32, i64, u8, f16, bf16, Complex32, Complex64);
+
+impl<E: MmapElement> CpuMmapTensor<E> {
+    /// ファイルの`offset`byte目から、row majorで`shape`の要素が並んでいる領域をメモリマップする。
+    /// 要素はコピーされず、読んだページだけがOSによって読み込まれる。
+    ///
+    /// # Safety
+    /// 返り値を使っている間、ファイルが他のプロセスなどから変更されたり切り詰められたりしてはいけない。
+    /// 変更された場合の動作は未定義になる。
+    pub unsafe fn open<T: AsRef<Path>>(
+        path: T,
+        offset: u64,
+        shape: Shape,
+        mode: MmapMode,
+    ) -> Result<Self, MmapError> {
+        shape.validate()?;
+        let align = std::mem::align_of::<E>();
+        // `u64::is_multiple_of`はRust 1.87からなので使わない
+        #[allow(clippy::manual_is_multiple_of)]
+        if offset % align as u64 != 0 {
+            return Err(MmapError::Misaligned { offset, align });
+        }
+        let num_elm = shape.num_elms();
+        let file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        let out_of_range = |end| MmapError::OutOfRange {
+            offset,
+            end,
+            file_len,
+        };
+        let bytes = num_elm
+            .checked_mul(std::mem::size_of::<E>())
+            .ok_or(out_of_range(u64::MAX))?;
+        let end = offset
+            .checked_add(bytes as u64)
+            .ok_or(out_of_range(u64::MAX))?;
+        if end > file_len {
+            return Err(out_of_range(end));
+        }
+        let mut options = MmapOptions::new();
+        options.offset(offset).len(bytes);
+        // 範囲とalignmentは上で確認しており、どのbit列も`E`として有効
+        let ptr = match mode {
+            MmapMode::ReadOnly => MmapCpu::from_mmap(options.map(&file)?, 0, num_elm),
+            MmapMode::CopyOnWrite => MmapCpu::from_mmap_copy(options.map_copy(&file)?, 0, num_elm),
+        };
+        let stride = shape.default_stride();
+        Ok(TensorBase {
+            ptr,
+            shape,
+            stride,
+            num_elm,
+        })
+    }
+
+    pub fn mode(&self) -> MmapMode {
+        self.ptr.mode()
+    }
+
+    /// ファイルのメモリを参照するviewを返す。BLASの関数などにコピーせずに渡せる。
+    #[inline]
+    pub fn to_view(&self) -> CpuViewTensor<'_, E> {
+        TensorBase {
+            ptr: self.ptr.access_by_offset_region(0, self.ptr.len()),
+            shape: self.shape.clone(),
+            stride: self.stride.clone(),
+            num_elm: self.num_elm,
+        }
+    }
+
+    /// indexが範囲外の場合や、軸の数が一致しない場合はエラーを返す。
+    #[inline]
+    pub fn try_slice(&self, index: TensorIndex) -> Result<CpuViewTensor<'_, E>, TensorError> {
+        let offset = try_slice_update_offset(&self.shape, &self.stride, &index)?;
+        let (shape, stride) = try_slice_update_shape_stride(&self.shape, &self.stride, &index)?;
+        let offset: usize = offset.try_into().unwrap();
+        let ptr = self
+            .ptr
+            .access_by_offset_region(offset, self.ptr.len() - offset);
+        Ok(TensorBase {
+            ptr,
+            shape,
+            stride,
+            num_elm: self.num_elm,
+        })
+    }
+
+    #[inline]
+    pub fn slice(&self, index: TensorIndex) -> CpuViewTensor<'_, E> {
+        self.try_slice(index).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// メモリを可変で借用するviewを返す。`ReadOnly`の場合はエラーになる。
+    /// 書き込んだ内容はファイルには反映されない。
+    #[inline]
+    pub fn try_to_view_mut(&mut self) -> Result<CpuViewMutTensor<'_, E>, MmapError> {
+        let ptr = self.ptr.to_view_mut().ok_or(MmapError::ReadOnly)?;
+        Ok(TensorBase {
+            ptr,
+            shape: self.shape.clone(),
+            stride: self.stride.clone(),
+            num_elm: self.num_elm,
+        })
+    }
+
+    #[inline]
+    pub fn to_view_mut(&mut self) -> CpuViewMutTensor<'_, E> {
+        self.try_to_view_mut().unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+#[cfg(test)]
+use crate::blas::cpu::{asum, gemm};
+#[cfg(test)]
+use crate::blas::CpuTranspose;
+#[cfg(test)]
+use crate::index;
+#[cfg(test)]
+use crate::tensor::CpuTensor;
+
+#[cfg(test)]
+fn write_temp_file(name: &str, values: &[f64]) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("{}_{}.bin", name, std::process::id()));
+    // 先頭8byteはheaderの代わりに0で埋める
+    let mut bytes = vec![0u8; 8];
+    for v in values {
+        bytes.extend_from_slice(&v.to_ne_bytes());
+    }
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
+
+#[test]
+fn mmap_read_only_test() {
+    let values = (0..12).map(|x| x as f64).collect::<Vec<_>>();
+    let path = write_temp_file("mmap_read_only_test", &values);
+    let a =
+        unsafe { CpuMmapTensor::<f64>::open(&path, 8, Shape::new(vec![3, 4]), MmapMode::ReadOnly) }
+            .unwrap();
+    assert_eq!(a.mode(), MmapMode::ReadOnly);
+    assert_eq!(a.iter().copied().collect::<Vec<_>>(), values);
+    let s = a.slice(index![1.., 1..;2]);
+    assert_eq!(s.shape(), Shape::new(vec![2, 2]));
+    assert_eq!(s.iter().copied().collect::<Vec<_>>(), vec![5., 7., 9., 11.]);
+    assert_eq!(unsafe { a.as_ptr().add(5) }, s.as_ptr());
+    assert_eq!(a.to_view().into_owned().to_vec(), values);
+
+    // BLASにはviewをそのまま渡せる
+    assert_eq!(asum(a.slice(index![1, ..])), Some(22.));
+    // column majorの4x3の行列として同じファイルをマップする
+    let m =
+        unsafe { CpuMmapTensor::<f64>::open(&path, 8, Shape::new(vec![4, 3]), MmapMode::ReadOnly) }
+            .unwrap();
+    let b = CpuTensor::from_vec(vec![1., 0., 1., 0., 1., 1.], Shape::new(vec![3, 2]));
+    let mut c = CpuTensor::<f64>::zeros(Shape::new(vec![4, 2]));
+    gemm(
+        CpuTranspose::None,
+        CpuTranspose::None,
+        1.,
+        0.,
+        m.to_view(),
+        b.to_view(),
+        c.to_view_mut(),
+    )
+    .unwrap();
+    assert_eq!(c.to_vec(), vec![8., 10., 12., 14., 12., 14., 16., 18.]);
+
+    let mut a = a;
+    assert!(matches!(a.try_to_view_mut(), Err(MmapError::ReadOnly)));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn mmap_copy_on_write_test() {
+    let path = write_temp_file("mmap_copy_on_write_test", &[1., 2., 3., 4.]);
+    let mut a =
+        unsafe { CpuMmapTensor::<f64>::open(&path, 8, Shape::new(vec![4]), MmapMode::CopyOnWrite) }
+            .unwrap();
+    a.to_view_mut().to_slice_mut()[1] = 20.;
+    assert_eq!(a.to_view().to_vec(), vec![1., 20., 3., 4.]);
+    // ファイルは変更されない
+    let b =
+        unsafe { CpuMmapTensor::<f64>::open(&path, 8, Shape::new(vec![4]), MmapMode::ReadOnly) }
+            .unwrap();
+    assert_eq!(b.to_view().to_vec(), vec![1., 2., 3., 4.]);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn mmap_open_error_test() {
+    let path = write_temp_file("mmap_open_error_test", &[1., 2.]);
+    assert!(matches!(
+        unsafe { CpuMmapTensor::<f64>::open(&path, 8, Shape::new(vec![3]), MmapMode::ReadOnly) },
+        Err(MmapError::OutOfRange { .. })
+    ));
+    assert!(matches!(
+        unsafe { CpuMmapTensor::<f64>::open(&path, 4, Shape::new(vec![1]), MmapMode::ReadOnly) },
+        Err(MmapError::Misaligned {
+            offset: 4,
+            align: 8
+        })
+    ));
+    // byte数がusizeに収まらない場合もエラーになる
+    assert!(matches!(
+        unsafe {
+            CpuMmapTensor::<f64>::open(&path, 8, Shape::new(vec![1 << 61]), MmapMode::ReadOnly)
+        },
+        Err(MmapError::OutOfRange { .. })
+    ));
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,221 @@
+use std::ptr::NonNull;
+
+use ndarray::{ArrayD, ArrayViewD, Axis, IxDyn, ShapeBuilder};
+
+use crate::error::TensorError;
+use crate::pointer_cpu::{OwnedCpu, ViewCpu};
+use crate::pointer_traits::{Cpu, Owned, TensorPointer};
+use crate::shape::{Shape, Stride};
+use crate::tensor::{CpuTensor, CpuViewTensor, TensorBase};
+
+/// 先頭の要素から見た、最も小さいoffsetと最も大きいoffset
+/// 要素が無い場合はメモリにアクセスしないので`(0, 0)`を返す。
+fn offset_range(shape: &[usize], stride: &[isize]) -> (isize, isize) {
+    if shape.contains(&0) {
+        return (0, 0);
+    }
+    let mut min = 0;
+    let mut max = 0;
+    for (dim, st) in shape.iter().zip(stride) {
+        let last = (*dim as isize - 1) * st;
+        if last < 0 {
+            min += last;
+        } else {
+            max += last;
+        }
+    }
+    (min, max)
+}
+
+/// メモリをコピーせずに`ArrayD`へ変換する。shapeとstrideはそのまま引き継ぐ。
+impl<E: Copy> From<CpuTensor<E>> for ArrayD<E> {
+    fn from(a: CpuTensor<E>) -> Self {
+        let shape = a.shape.iter().map(|d| *d as usize).collect::<Vec<_>>();
+        let stride = a.stride.iter().map(|s| *s as usize).collect::<Vec<_>>();
+        let v = a.ptr.into_vec();
+        ArrayD::from_shape_vec(IxDyn(&shape).strides(IxDyn(&stride)), v)
+            .unwrap_or_else(|e| panic!("internal error, invalid layout of owned tensor: {}", e))
+    }
+}
+
+/// `ArrayD`のメモリをそのまま使って`CpuTensor`に変換する。
+/// 先頭の要素がメモリの先頭にない場合や、要素の間に隙間がある場合、負のstrideを持つ場合はrow majorにコピーする。
+/// 0次元の`ArrayD`は変換できない。
+impl<E: Copy> TryFrom<ArrayD<E>> for CpuTensor<E> {
+    type Error = TensorError;
+
+    fn try_from(a: ArrayD<E>) -> Result<Self, Self::Error> {
+        let shape = Shape::new(a.shape().iter().map(|d| *d as isize).collect());
+        shape.validate()?;
+        let stride = a.strides().to_vec();
+        if stride.iter().any(|s| *s < 0) {
+            return Self::try_from(a.as_standard_layout().into_owned());
+        }
+        let dim = a.raw_dim();
+        let first = a.as_ptr();
+        let v = a.into_raw_vec();
+        // 要素がメモリを隙間なく埋めている場合(row majorや軸を並べ替えたもの)だけそのまま使う
+        if !std::ptr::eq(first, v.as_ptr()) || v.len() != shape.num_elms() {
+            // vがメモリを持っている間に、元の配置のまま要素を読み出す
+            let abs_stride = IxDyn(&stride.iter().map(|s| *s as usize).collect::<Vec<_>>());
+            let view = unsafe { ArrayViewD::from_shape_ptr(dim.strides(abs_stride), first) };
+            return Self::try_from_vec(view.iter().copied().collect(), shape);
+        }
+        let num_elm = v.len();
+        Ok(TensorBase {
+            ptr: OwnedCpu::from_vec(v),
+            shape,
+            stride: Stride::new(stride),
+            num_elm,
+        })
+    }
+}
+
+/// メモリを借用する`ArrayViewD`に変換する。負のstrideやbroadcastした軸もそのまま表せる。
+impl<'a, P, E> From<&'a TensorBase<P, E>> for ArrayViewD<'a, E>
+where
+    P: TensorPointer<Elem = E> + Cpu,
+{
+    fn from(a: &'a TensorBase<P, E>) -> Self {
+        let shape = a.shape.iter().map(|d| *d as usize).collect::<Vec<_>>();
+        let abs_stride = a
+            .stride
+            .iter()
+            .map(|s| s.unsigned_abs())
+            .collect::<Vec<_>>();
+        let (min, _) = offset_range(&shape, &a.stride);
+        // ndarrayは負のstrideを直接受け取れないため、最も小さいアドレスから正のstrideで作って軸を反転する
+        let mut view = unsafe {
+            ArrayViewD::from_shape_ptr(
+                IxDyn(&shape).strides(IxDyn(&abs_stride)),
+                a.ptr.as_ptr().offset(min),
+            )
+        };
+        for (axis, st) in a.stride.iter().enumerate() {
+            if *st < 0 {
+                view.invert_axis(Axis(axis));
+            }
+        }
+        view
+    }
+}
+
+/// `ArrayViewD`が指すメモリを借用する`CpuViewTensor`に変換する。
+/// 0次元の場合は変換できない。
+impl<'a, E: Copy> TryFrom<ArrayViewD<'a, E>> for CpuViewTensor<'a, E> {
+    type Error = TensorError;
+
+    fn try_from(a: ArrayViewD<'a, E>) -> Result<Self, Self::Error> {
+        let shape = Shape::new(a.shape().iter().map(|d| *d as isize).collect());
+        shape.validate()?;
+        let stride = a.strides().to_vec();
+        let (min, max) = offset_range(a.shape(), &stride);
+        let len = if shape.num_elms() == 0 {
+            0
+        } else {
+            (max - min + 1) as usize
+        };
+        let base = unsafe { NonNull::new_unchecked(a.as_ptr().offset(min) as *mut E) };
+        let ptr = unsafe { ViewCpu::from_raw_parts(base, (-min) as usize, len)? };
+        Ok(TensorBase {
+            ptr,
+            shape,
+            stride: Stride::new(stride),
+            num_elm: len,
+        })
+    }
+}
+
+#[cfg(test)]
+use crate::index;
+#[cfg(test)]
+use ndarray::{s, Array};
+
+#[test]
+fn owned_round_trip_test() {
+    let a = CpuTensor::from_vec((0..6).collect::<Vec<i32>>(), Shape::new(vec![2, 3]));
+    let ptr = a.as_ptr();
+    let arr = ArrayD::from(a);
+    assert_eq!(arr.shape(), &[2, 3]);
+    assert_eq!(arr[[1, 2]], 5);
+    assert_eq!(arr.as_ptr(), ptr);
+    let b = CpuTensor::try_from(arr).unwrap();
+    assert_eq!(b.as_ptr(), ptr);
+    assert_eq!(b.shape(), Shape::new(vec![2, 3]));
+    assert_eq!(b.to_vec(), (0..6).collect::<Vec<i32>>());
+}
+
+#[test]
+fn owned_from_strided_array_test() {
+    let arr = Array::from_shape_vec((2, 3), (0..6).collect::<Vec<i32>>())
+        .unwrap()
+        .into_dyn();
+    // 転置はメモリを共有したままstrideだけ変わる
+    let t = CpuTensor::try_from(arr.clone().reversed_axes()).unwrap();
+    assert_eq!(t.stride_vec(), vec![1, 3]);
+    assert_eq!(
+        t.iter().copied().collect::<Vec<_>>(),
+        vec![0, 3, 1, 4, 2, 5]
+    );
+    // 負のstrideはコピーする
+    let mut inv = arr.clone();
+    inv.invert_axis(Axis(1));
+    let inv = CpuTensor::try_from(inv).unwrap();
+    assert_eq!(inv.to_vec(), vec![2, 1, 0, 5, 4, 3]);
+    // 先頭がずれている場合もコピーする
+    let sliced = arr.clone().slice_move(s![1.., ..]).into_dyn();
+    assert_eq!(CpuTensor::try_from(sliced).unwrap().to_vec(), vec![3, 4, 5]);
+    // 先頭が同じでも要素の間に隙間がある場合はコピーする
+    let arr = Array::from_shape_vec((3, 4), (0..12).collect::<Vec<i32>>()).unwrap();
+    let gapped = CpuTensor::try_from(arr.slice_move(s![..;2, ..]).into_dyn()).unwrap();
+    assert_eq!(gapped.shape(), Shape::new(vec![2, 4]));
+    assert_eq!(gapped.to_vec(), vec![0, 1, 2, 3, 8, 9, 10, 11]);
+    let scalar = ArrayD::from_elem(IxDyn(&[]), 1);
+    assert!(CpuTensor::try_from(scalar).is_err());
+}
+
+#[test]
+fn view_round_trip_test() {
+    let a = CpuTensor::from_vec((0..12).collect::<Vec<i32>>(), Shape::new(vec![3, 4]));
+    let v = a.slice(index![1.., ..;-2]);
+    let arr = ArrayViewD::from(&v);
+    assert_eq!(arr.shape(), &[2, 2]);
+    assert_eq!(arr.iter().copied().collect::<Vec<_>>(), vec![7, 5, 11, 9]);
+    let back = CpuViewTensor::try_from(arr).unwrap();
+    assert_eq!(back.iter().copied().collect::<Vec<_>>(), vec![7, 5, 11, 9]);
+    assert_eq!(back.as_ptr(), v.as_ptr());
+}
+
+#[test]
+fn view_from_array_view_test() {
+    let arr = Array::from_shape_vec((3, 4), (0..12).collect::<Vec<i32>>()).unwrap();
+    let sliced = arr.slice(s![..;2, 1..3]).into_dyn();
+    let v = CpuViewTensor::try_from(sliced).unwrap();
+    assert_eq!(v.shape(), Shape::new(vec![2, 2]));
+    assert_eq!(v.iter().copied().collect::<Vec<_>>(), vec![1, 2, 9, 10]);
+    let b = CpuTensor::from_vec(vec![1, 2], Shape::new(vec![2]));
+    let b = b.broadcast_to(Shape::new(vec![3, 2])).unwrap();
+    let b = ArrayViewD::from(&b);
+    assert_eq!(b.strides(), &[0, 1]);
+    assert_eq!(
+        b.iter().copied().collect::<Vec<_>>(),
+        vec![1, 2, 1, 2, 1, 2]
+    );
+}
+
+#[test]
+fn empty_array_test() {
+    let a = CpuTensor::<f32>::zeros(Shape::new(vec![0, 3]));
+    let v = ArrayViewD::from(&a);
+    assert_eq!(v.shape(), &[0, 3]);
+    assert_eq!(v.len(), 0);
+    let back = CpuViewTensor::try_from(v).unwrap();
+    assert_eq!(back.shape(), Shape::new(vec![0, 3]));
+    let arr = ArrayD::<f32>::zeros(IxDyn(&[2, 0]));
+    let b = CpuTensor::try_from(arr.clone()).unwrap();
+    assert_eq!(b.shape(), Shape::new(vec![2, 0]));
+    assert!(b.to_vec().is_empty());
+    let mut inv = arr;
+    inv.invert_axis(Axis(0));
+    assert!(CpuTensor::try_from(inv).unwrap().to_vec().is_empty());
+}
@@ -0,0 +1,609 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::path::Path;
+
+use half::{bf16, f16};
+use thiserror::Error;
+use zip::write::FileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use crate::dyn_tensor::DynTensor;
+use crate::error::TensorError;
+use crate::pointer_cpu::OwnedCpu;
+use crate::pointer_traits::{Cpu, Owned, TensorPointer};
+use crate::shape::{Shape, Stride};
+use crate::tensor::{CpuTensor, TensorBase};
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// `.npy`, `.npz`の読み書きで起こるエラー
+#[derive(Error, Debug)]
+pub enum NpyError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Zip(#[from] zip::result::ZipError),
+
+    #[error(transparent)]
+    Tensor(#[from] TensorError),
+
+    #[error("invalid npy header: {0}")]
+    InvalidHeader(String),
+
+    #[error("dtype mismatch: file has {got}, but expected {expected}")]
+    DtypeMismatch { expected: String, got: String },
+
+    #[error("unsupported dtype {0}")]
+    UnsupportedDtype(String),
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// リトルエンディアン、ビッグエンディアンのバイト列と変換できる要素の型
+/// `SafeTensorsReader::view`などはバイト列をそのまま要素として読むため、crateの外からは実装できない。
+///
+/// ```compile_fail
+/// use bokutotu::npy::ElementBytes;
+///
+/// #[derive(Clone, Copy)]
+/// struct Char(char);
+///
+/// impl ElementBytes for Char {
+///     const SIZE: usize = 4;
+///     fn from_le_bytes(_: &[u8]) -> Self { Char('a') }
+///     fn from_be_bytes(_: &[u8]) -> Self { Char('a') }
+///     fn write_le_bytes(self, _: &mut Vec<u8>) {}
+/// }
+/// ```
+pub trait ElementBytes: Copy + sealed::Sealed {
+    const SIZE: usize;
+
+    fn from_le_bytes(bytes: &[u8]) -> Self;
+    fn from_be_bytes(bytes: &[u8]) -> Self;
+    fn write_le_bytes(self, out: &mut Vec<u8>);
+}
+
+/// `.npy`に保存できる要素の型
+pub trait NpyElement: ElementBytes {
+    /// NumPyのdtypeの種類を表す文字(`f`, `i`, `u`, `b`)
+    const KIND: char;
+}
+
+macro_rules! impl_element_bytes {
+    ( $( $ty:ty ),* ) => {
+        $(
+            impl sealed::Sealed for $ty {}
+
+            impl ElementBytes for $ty {
+                const SIZE: usize = std::mem::size_of::<$ty>();
+
+                #[inline]
+                fn from_le_bytes(bytes: &[u8]) -> Self {
+                    <$ty>::from_le_bytes(bytes.try_into().unwrap())
+                }
+
+                #[inline]
+                fn from_be_bytes(bytes: &[u8]) -> Self {
+                    <$ty>::from_be_bytes(bytes.try_into().unwrap())
+                }
+
+                #[inline]
+                fn write_le_bytes(self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_npy_element {
+    ( $( ($ty:ty, $kind:expr) ),* ) => {
+        $(
+            impl NpyElement for $ty {
+                const KIND: char = $kind;
+            }
+        )*
+    };
+}
+
+impl_element_bytes!(f32, f64, i8, i16, i32, i64, u8, f16, bf16);
+impl_npy_element!(
+    (f32, 'f'),
+    (f64, 'f'),
+    (i8, 'i'),
+    (i16, 'i'),
+    (i32, 'i'),
+    (i64, 'i'),
+    (u8, 'u'),
+    (f16, 'f'),
+    (bool, 'b')
+);
+
+impl sealed::Sealed for bool {}
+
+impl ElementBytes for bool {
+    const SIZE: usize = 1;
+
+    #[inline]
+    fn from_le_bytes(bytes: &[u8]) -> Self {
+        bytes[0] != 0
+    }
+
+    #[inline]
+    fn from_be_bytes(bytes: &[u8]) -> Self {
+        bytes[0] != 0
+    }
+
+    #[inline]
+    fn write_le_bytes(self, out: &mut Vec<u8>) {
+        out.push(self as u8);
+    }
+}
+
+/// 書き込む時のdtype。1byteの型はbyte orderを持たない。
+fn descr<E: NpyElement>() -> String {
+    let order = if E::SIZE == 1 { '|' } else { '<' };
+    format!("{}{}{}", order, E::KIND, E::SIZE)
+}
+
+struct Header {
+    descr: String,
+    fortran_order: bool,
+    shape: Vec<isize>,
+}
+
+/// headerの辞書から`key`の値の部分を取り出す。
+fn header_value<'a>(dict: &'a str, key: &str) -> Result<&'a str, NpyError> {
+    let pattern = format!("'{}':", key);
+    let start = dict
+        .find(&pattern)
+        .ok_or_else(|| NpyError::InvalidHeader(format!("missing key '{}'", key)))?;
+    Ok(dict[start + pattern.len()..].trim_start())
+}
+
+fn parse_header(dict: &str) -> Result<Header, NpyError> {
+    let invalid = |what: &str| NpyError::InvalidHeader(format!("cannot parse {}", what));
+
+    let descr = header_value(dict, "descr")?;
+    let descr = descr
+        .strip_prefix('\'')
+        .and_then(|s| s.split('\'').next())
+        .ok_or_else(|| invalid("descr"))?
+        .to_string();
+
+    let fortran_order = header_value(dict, "fortran_order")?;
+    let fortran_order = if fortran_order.starts_with("True") {
+        true
+    } else if fortran_order.starts_with("False") {
+        false
+    } else {
+        return Err(invalid("fortran_order"));
+    };
+
+    let shape = header_value(dict, "shape")?;
+    let shape = shape
+        .strip_prefix('(')
+        .and_then(|s| s.split(')').next())
+        .ok_or_else(|| invalid("shape"))?;
+    let shape = shape
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<isize>().map_err(|_| invalid("shape")))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Header {
+        descr,
+        fortran_order,
+        shape,
+    })
+}
+
+fn read_header<R: Read>(reader: &mut R) -> Result<Header, NpyError> {
+    let mut magic = [0; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic[..6] != MAGIC {
+        return Err(NpyError::InvalidHeader("not a npy file".to_string()));
+    }
+    let header_len = match magic[6] {
+        1 => {
+            let mut len = [0; 2];
+            reader.read_exact(&mut len)?;
+            u16::from_le_bytes(len) as usize
+        }
+        2 | 3 => {
+            let mut len = [0; 4];
+            reader.read_exact(&mut len)?;
+            u32::from_le_bytes(len) as usize
+        }
+        v => {
+            return Err(NpyError::InvalidHeader(format!(
+                "unsupported version {}",
+                v
+            )))
+        }
+    };
+    let mut dict = vec![0; header_len];
+    reader.read_exact(&mut dict)?;
+    let dict = String::from_utf8(dict)
+        .map_err(|_| NpyError::InvalidHeader("header is not utf-8".to_string()))?;
+    parse_header(&dict)
+}
+
+/// column majorのstride
+fn fortran_stride(shape: &[isize]) -> Stride {
+    let mut stride = Vec::with_capacity(shape.len());
+    let mut st = 1;
+    for dim in shape {
+        stride.push(st);
+        st *= dim;
+    }
+    Stride::new(stride)
+}
+
+/// headerのdtypeのbyte orderがbig endianかどうか
+fn is_big_endian(descr: &str) -> Result<bool, NpyError> {
+    match descr.get(..1) {
+        Some("<") | Some("|") => Ok(false),
+        Some(">") => Ok(true),
+        Some("=") => Ok(cfg!(target_endian = "big")),
+        _ => Err(NpyError::InvalidHeader(format!(
+            "unknown byte order in '{}'",
+            descr
+        ))),
+    }
+}
+
+/// headerに続くデータを読み込む。dtypeは確認済みでなければならない。
+fn read_data<R: Read, E: NpyElement>(
+    reader: &mut R,
+    header: Header,
+) -> Result<CpuTensor<E>, NpyError> {
+    let big_endian = is_big_endian(&header.descr)?;
+    let shape = Shape::new(header.shape);
+    shape.validate()?;
+    // shapeは信頼できないため、要素数とbyte数のoverflowを確認する
+    let too_large = || NpyError::InvalidHeader(format!("shape {:?} is too large", shape));
+    let num_elm = shape
+        .iter()
+        .try_fold(1usize, |acc, dim| acc.checked_mul(*dim as usize))
+        .filter(|n| *n <= isize::MAX as usize)
+        .ok_or_else(too_large)?;
+    let len = num_elm.checked_mul(E::SIZE).ok_or_else(too_large)?;
+    // 先にlen byteを確保せず、実際に読めた分だけ確保する
+    let mut bytes = Vec::new();
+    Read::take(&mut *reader, len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+    let v = bytes
+        .chunks_exact(E::SIZE)
+        .map(|b| {
+            if big_endian {
+                E::from_be_bytes(b)
+            } else {
+                E::from_le_bytes(b)
+            }
+        })
+        .collect::<Vec<E>>();
+
+    if header.fortran_order {
+        let stride = fortran_stride(&shape);
+        Ok(TensorBase {
+            ptr: OwnedCpu::from_vec(v),
+            shape,
+            stride,
+            num_elm,
+        })
+    } else {
+        Ok(CpuTensor::try_from_vec(v, shape)?)
+    }
+}
+
+/// `.npy`の形式で書かれたデータを読み込む。
+/// big endianのデータも読み込める。Fortran orderのデータはcolumn majorのstrideを持つtensorになる。
+pub fn read_npy_from<R: Read, E: NpyElement>(reader: &mut R) -> Result<CpuTensor<E>, NpyError> {
+    let header = read_header(reader)?;
+    let expected = descr::<E>();
+    if header.descr.get(1..) != Some(&expected[1..]) {
+        return Err(NpyError::DtypeMismatch {
+            expected,
+            got: header.descr,
+        });
+    }
+    read_data(reader, header)
+}
+
+/// `.npy`の形式で書かれたデータを、headerのdtypeに合わせて読み込む。
+pub fn read_npy_dyn_from<R: Read>(reader: &mut R) -> Result<DynTensor, NpyError> {
+    let header = read_header(reader)?;
+    let ty = header.descr.get(1..).unwrap_or_default().to_string();
+    Ok(match ty.as_str() {
+        "f2" => read_data::<_, f16>(reader, header)?.into(),
+        "f4" => read_data::<_, f32>(reader, header)?.into(),
+        "f8" => read_data::<_, f64>(reader, header)?.into(),
+        "i1" => read_data::<_, i8>(reader, header)?.into(),
+        "i2" => read_data::<_, i16>(reader, header)?.into(),
+        "i4" => read_data::<_, i32>(reader, header)?.into(),
+        "i8" => read_data::<_, i64>(reader, header)?.into(),
+        "u1" => read_data::<_, u8>(reader, header)?.into(),
+        "b1" => read_data::<_, bool>(reader, header)?.into(),
+        _ => return Err(NpyError::UnsupportedDtype(header.descr)),
+    })
+}
+
+/// `.npy`の形式で書き込む。strideに関わらず、row majorの順に書き込む。
+pub fn write_npy_to<W, P, E>(writer: &mut W, a: &TensorBase<P, E>) -> Result<(), NpyError>
+where
+    W: Write,
+    P: TensorPointer<Elem = E> + Cpu,
+    E: NpyElement,
+{
+    let shape = a
+        .shape
+        .iter()
+        .map(|d| format!("{},", d))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut dict = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': ({}), }}",
+        descr::<E>(),
+        shape
+    );
+    // magic(6) + version(2) + header長(2) + headerが64の倍数になるよう空白で埋め、改行で終える
+    let unpadded = MAGIC.len() + 4 + dict.len() + 1;
+    let total = unpadded.div_ceil(64) * 64;
+    dict.push_str(&" ".repeat(total - unpadded));
+    dict.push('\n');
+
+    let mut bytes = Vec::with_capacity(total + a.shape.num_elms() * E::SIZE);
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&[1, 0]);
+    bytes.extend_from_slice(&(dict.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(dict.as_bytes());
+    for x in a.iter() {
+        x.write_le_bytes(&mut bytes);
+    }
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+impl<E: NpyElement> CpuTensor<E> {
+    /// `.npy`ファイルを読み込む。
+    pub fn read_npy<T: AsRef<Path>>(path: T) -> Result<Self, NpyError> {
+        let mut reader = BufReader::new(File::open(path)?);
+        read_npy_from(&mut reader)
+    }
+}
+
+impl DynTensor {
+    /// `.npy`ファイルを、要素の型をファイルのdtypeに合わせて読み込む。
+    pub fn read_npy<T: AsRef<Path>>(path: T) -> Result<Self, NpyError> {
+        let mut reader = BufReader::new(File::open(path)?);
+        read_npy_dyn_from(&mut reader)
+    }
+}
+
+impl<P, E> TensorBase<P, E>
+where
+    P: TensorPointer<Elem = E> + Cpu,
+    E: NpyElement,
+{
+    /// `.npy`ファイルに書き込む。viewの場合も連続したデータとして書き込む。
+    pub fn write_npy<T: AsRef<Path>>(&self, path: T) -> Result<(), NpyError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        write_npy_to(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// `.npz`ファイルから、名前を指定してtensorを読み込む。
+pub struct NpzReader<R: Read + Seek> {
+    archive: ZipArchive<R>,
+}
+
+impl NpzReader<BufReader<File>> {
+    pub fn open<T: AsRef<Path>>(path: T) -> Result<Self, NpyError> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read + Seek> NpzReader<R> {
+    pub fn new(reader: R) -> Result<Self, NpyError> {
+        Ok(Self {
+            archive: ZipArchive::new(reader)?,
+        })
+    }
+
+    /// 含まれているtensorの名前
+    pub fn names(&self) -> Vec<String> {
+        self.archive
+            .file_names()
+            .map(|name| name.strip_suffix(".npy").unwrap_or(name).to_string())
+            .collect()
+    }
+
+    pub fn read<E: NpyElement>(&mut self, name: &str) -> Result<CpuTensor<E>, NpyError> {
+        let mut file = self.archive.by_name(&format!("{}.npy", name))?;
+        read_npy_from(&mut file)
+    }
+
+    /// 要素の型をファイルのdtypeに合わせて読み込む。
+    pub fn read_dyn(&mut self, name: &str) -> Result<DynTensor, NpyError> {
+        let mut file = self.archive.by_name(&format!("{}.npy", name))?;
+        read_npy_dyn_from(&mut file)
+    }
+}
+
+/// `.npz`ファイルに名前を付けてtensorを書き込む。型の異なるtensorを混ぜて書き込める。
+/// NumPyの`savez`と同じく、圧縮はしない。
+pub struct NpzWriter<W: Write + Seek> {
+    zip: ZipWriter<W>,
+}
+
+impl NpzWriter<BufWriter<File>> {
+    pub fn create<T: AsRef<Path>>(path: T) -> Result<Self, NpyError> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write + Seek> NpzWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            zip: ZipWriter::new(writer),
+        }
+    }
+
+    pub fn add<P, E>(&mut self, name: &str, a: &TensorBase<P, E>) -> Result<(), NpyError>
+    where
+        P: TensorPointer<Elem = E> + Cpu,
+        E: NpyElement,
+    {
+        let options = FileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored)
+            .large_file(true);
+        self.zip.start_file(format!("{}.npy", name), options)?;
+        write_npy_to(&mut self.zip, a)
+    }
+
+    /// zipの末尾を書き込んで、内部のwriterを返す。
+    pub fn finish(mut self) -> Result<W, NpyError> {
+        Ok(self.zip.finish()?)
+    }
+}
+
+#[cfg(test)]
+use crate::index;
+#[cfg(test)]
+use std::io::Cursor;
+
+#[test]
+fn npy_round_trip_test() {
+    let a = CpuTensor::from_vec(vec![1.5f32, -2., 3., 4., 5., 6.], Shape::new(vec![2, 3]));
+    let mut buf = Vec::new();
+    write_npy_to(&mut buf, &a).unwrap();
+    // headerを含めて64byteに揃える
+    assert_eq!((buf.len() - 6 * 4) % 64, 0);
+    let b: CpuTensor<f32> = read_npy_from(&mut Cursor::new(buf)).unwrap();
+    assert_eq!(b.shape(), a.shape());
+    assert_eq!(b.to_vec(), a.to_vec());
+
+    let c = CpuTensor::from_vec(vec![true, false, true], Shape::new(vec![3]));
+    let mut buf = Vec::new();
+    write_npy_to(&mut buf, &c).unwrap();
+    let d: CpuTensor<bool> = read_npy_from(&mut Cursor::new(buf.clone())).unwrap();
+    assert_eq!(d.to_vec(), vec![true, false, true]);
+    let e = read_npy_from::<_, u8>(&mut Cursor::new(buf));
+    assert!(matches!(e, Err(NpyError::DtypeMismatch { .. })));
+}
+
+#[test]
+fn npy_write_view_test() {
+    let a = CpuTensor::from_vec((0..6).collect::<Vec<i64>>(), Shape::new(vec![2, 3]));
+    let v = a.slice(index![.., ..;-2]);
+    let mut buf = Vec::new();
+    write_npy_to(&mut buf, &v).unwrap();
+    let b: CpuTensor<i64> = read_npy_from(&mut Cursor::new(buf)).unwrap();
+    assert_eq!(b.shape(), Shape::new(vec![2, 2]));
+    assert_eq!(b.to_vec(), vec![2, 0, 5, 3]);
+}
+
+#[cfg(test)]
+fn npy_bytes(dict: &str, data: &[u8]) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend_from_slice(&[1, 0]);
+    bytes.extend_from_slice(&(dict.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(dict.as_bytes());
+    bytes.extend_from_slice(data);
+    bytes
+}
+
+#[test]
+fn npy_big_endian_fortran_test() {
+    let data = [1i32, 2, 3, 4, 5, 6]
+        .iter()
+        .flat_map(|x| x.to_be_bytes())
+        .collect::<Vec<u8>>();
+    let bytes = npy_bytes(
+        "{'descr': '>i4', 'fortran_order': True, 'shape': (2, 3), }\n",
+        &data,
+    );
+    let a: CpuTensor<i32> = read_npy_from(&mut Cursor::new(bytes)).unwrap();
+    assert_eq!(a.shape(), Shape::new(vec![2, 3]));
+    assert_eq!(a.stride_vec(), vec![1, 2]);
+    assert_eq!(
+        a.iter().copied().collect::<Vec<_>>(),
+        vec![1, 3, 5, 2, 4, 6]
+    );
+}
+
+#[test]
+fn npy_invalid_header_test() {
+    let e = read_npy_from::<_, f32>(&mut Cursor::new(b"not a npy file".to_vec()));
+    assert!(matches!(e, Err(NpyError::InvalidHeader(_))));
+    let bytes = npy_bytes("{'descr': '<f4', 'shape': (2,), }\n", &[0; 8]);
+    let e = read_npy_from::<_, f32>(&mut Cursor::new(bytes));
+    assert!(matches!(e, Err(NpyError::InvalidHeader(_))));
+    // 要素数がoverflowするshape
+    let bytes = npy_bytes(
+        "{'descr': '<f4', 'fortran_order': False, 'shape': (4611686018427387904, 4), }\n",
+        &[0; 8],
+    );
+    let e = read_npy_from::<_, f32>(&mut Cursor::new(bytes));
+    assert!(matches!(e, Err(NpyError::InvalidHeader(_))));
+    // データが足りない場合は、shapeの分のメモリを確保せずにエラーを返す
+    let bytes = npy_bytes(
+        "{'descr': '<f4', 'fortran_order': False, 'shape': (1099511627776,), }\n",
+        &[0; 8],
+    );
+    let e = read_npy_from::<_, f32>(&mut Cursor::new(bytes));
+    assert!(matches!(e, Err(NpyError::Io(_))));
+}
+
+#[test]
+fn npz_round_trip_test() {
+    let a = CpuTensor::from_vec(vec![1f64, 2., 3.], Shape::new(vec![3]));
+    let b = CpuTensor::from_vec(vec![1u8, 2, 3, 4], Shape::new(vec![2, 2]));
+    let mut writer = NpzWriter::new(Cursor::new(Vec::new()));
+    writer.add("a", &a).unwrap();
+    writer.add("b", &b.t()).unwrap();
+    let buf = writer.finish().unwrap();
+
+    let mut reader = NpzReader::new(buf).unwrap();
+    let mut names = reader.names();
+    names.sort();
+    assert_eq!(names, vec!["a", "b"]);
+    assert_eq!(reader.read::<f64>("a").unwrap().to_vec(), vec![1., 2., 3.]);
+    assert_eq!(reader.read::<u8>("b").unwrap().to_vec(), vec![1, 3, 2, 4]);
+    assert!(reader.read::<f64>("c").is_err());
+}
+
+#[test]
+fn npy_file_test() {
+    let path = std::env::temp_dir().join(format!("npy_file_test_{}.npy", std::process::id()));
+    let a = CpuTensor::from_vec(vec![1i32, 2, 3, 4], Shape::new(vec![2, 2]));
+    a.write_npy(&path).unwrap();
+    let b = CpuTensor::<i32>::read_npy(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(b.to_vec(), a.to_vec());
+}
+
+#[test]
+fn npy_dyn_test() {
+    let a = CpuTensor::from_vec(vec![1i16, -2, 3], Shape::new(vec![3]));
+    let mut buf = Vec::new();
+    write_npy_to(&mut buf, &a).unwrap();
+    let d = read_npy_dyn_from(&mut Cursor::new(buf)).unwrap();
+    assert_eq!(d.dtype(), crate::dyn_tensor::DType::I16);
+    assert_eq!(d.downcast::<i16>().unwrap().to_vec(), vec![1, -2, 3]);
+    let bytes = npy_bytes(
+        "{'descr': '<c8', 'fortran_order': False, 'shape': (1,), }\n",
+        &[0; 8],
+    );
+    let e = read_npy_dyn_from(&mut Cursor::new(bytes));
+    assert!(matches!(e, Err(NpyError::UnsupportedDtype(_))));
+}
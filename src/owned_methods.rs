@@ -1,10 +1,15 @@
-use std::convert::TryInto;
-
+use crate::error::TensorError;
 use crate::index::TensorIndex;
+use crate::pointer_cpu::{ArcCpu, OwnedCpu};
 use crate::pointer_traits::{Cpu, Owned, TensorPointer};
-use crate::shape::{slice_update_offset, slice_update_shape_stride};
-// use crate::tensor::{CpuTensor, CpuViewMutTensor, CpuViewTensor};
-use crate::tensor::TensorBase;
+use crate::shape::{
+    broadcast_update_stride, checked_offset, expand_update_shape_stride,
+    flatten_update_shape_stride, squeeze_update_shape_stride, transpose_axes,
+    try_permute_update_shape_stride, try_slice_update_offset, try_slice_update_shape_stride,
+    unflatten_update_shape_stride, unsqueeze_update_shape_stride, BroadcastError, Shape, Stride,
+};
+use crate::tensor::{CpuArcTensor, CpuCowTensor, CpuTensor, TensorBase};
+use crate::view_methods::{cpu_contiguous, cpu_reshape};
 
 impl<P, E> TensorBase<P, E>
 where
@@ -23,11 +28,20 @@ where
         }
     }
 
+    /// メモリを借用するviewを返す。viewを使っている間は`self`を変更したりdropしたりできない。
+    ///
+    /// ```compile_fail
+    /// use bokutotu::shape::Shape;
+    /// use bokutotu::tensor::CpuTensor;
+    ///
+    /// let v = {
+    ///     let a = CpuTensor::from_vec(vec![1f32, 2.], Shape::new(vec![2]));
+    ///     a.to_view()
+    /// };
+    /// println!("{:?}", v);
+    /// ```
     #[inline]
-    pub fn to_view(&self) -> TensorBase<<P as Owned>::View, E>
-    where
-        <P as Owned>::View: TensorPointer<Elem = E>,
-    {
+    pub fn to_view(&self) -> TensorBase<P::View<'_>, E> {
         let ptr = self.ptr.to_view(0);
         let shape = self.shape.clone();
         let stride = self.stride.clone();
@@ -40,11 +54,20 @@ where
         }
     }
 
+    /// メモリを可変で借用するviewを返す。viewを使っている間は`self`や他のviewを使えない。
+    ///
+    /// ```compile_fail
+    /// use bokutotu::shape::Shape;
+    /// use bokutotu::tensor::CpuTensor;
+    ///
+    /// let mut a = CpuTensor::from_vec(vec![1f32, 2.], Shape::new(vec![2]));
+    /// let mut v1 = a.to_view_mut();
+    /// let v2 = a.to_view();
+    /// v1.to_slice_mut()[0] = 3.;
+    /// println!("{:?}", v2);
+    /// ```
     #[inline]
-    pub fn to_view_mut(&mut self) -> TensorBase<<P as Owned>::ViewMut, E>
-    where
-        <P as Owned>::ViewMut: TensorPointer<Elem = E>,
-    {
+    pub fn to_view_mut(&mut self) -> TensorBase<P::ViewMut<'_>, E> {
         let ptr = self.ptr.to_view_mut(0);
         let shape = self.shape.clone();
         let stride = self.stride.clone();
@@ -57,40 +80,184 @@ where
         }
     }
 
+    /// indexで指定した範囲を借用するviewを返す。
+    /// indexが範囲外の場合や、軸の数が一致しない場合、要素を確保していない場合はエラーを返す。
     #[inline]
-    pub fn slice(&self, index: TensorIndex) -> TensorBase<<P as Owned>::View, E>
-    where
-        <P as Owned>::View: TensorPointer<Elem = E>,
-    {
-        let offset = slice_update_offset(&self.shape, &self.stride, &index);
-        let (shape, stride) = slice_update_shape_stride(&self.shape, &self.stride, &index);
-        let ptr = self.ptr.to_view(offset.try_into().unwrap());
+    pub fn try_slice(&self, index: TensorIndex) -> Result<TensorBase<P::View<'_>, E>, TensorError> {
+        let offset = try_slice_update_offset(&self.shape, &self.stride, &index)?;
+        let (shape, stride) = try_slice_update_shape_stride(&self.shape, &self.stride, &index)?;
+        let offset = checked_offset(0, offset, self.ptr.len())?;
+        let ptr = self.ptr.to_view(offset);
         let num_elm = self.num_elm;
-        TensorBase {
+        Ok(TensorBase {
             ptr,
             shape,
             stride,
             num_elm,
-        }
+        })
+    }
+
+    /// `try_slice`と同じだが、エラーの場合はpanicする。
+    #[inline]
+    pub fn slice(&self, index: TensorIndex) -> TensorBase<P::View<'_>, E> {
+        self.try_slice(index).unwrap_or_else(|e| panic!("{}", e))
     }
 
+    /// indexで指定した範囲を可変で借用するviewを返す。エラーになる条件は`try_slice`と同じ。
     #[inline]
-    pub fn slice_mut(&mut self, index: TensorIndex) -> TensorBase<<P as Owned>::ViewMut, E>
-    where
-        <P as Owned>::ViewMut: TensorPointer<Elem = E>,
-    {
-        let offset = slice_update_offset(&self.shape, &self.stride, &index);
-        let (shape, stride) = slice_update_shape_stride(&self.shape, &self.stride, &index);
-        let ptr = self.ptr.to_view_mut(offset.try_into().unwrap());
+    pub fn try_slice_mut(
+        &mut self,
+        index: TensorIndex,
+    ) -> Result<TensorBase<P::ViewMut<'_>, E>, TensorError> {
+        let offset = try_slice_update_offset(&self.shape, &self.stride, &index)?;
+        let (shape, stride) = try_slice_update_shape_stride(&self.shape, &self.stride, &index)?;
+        let offset = checked_offset(0, offset, self.ptr.len())?;
+        let ptr = self.ptr.to_view_mut(offset);
         let num_elm = self.num_elm;
-        TensorBase {
+        Ok(TensorBase {
+            ptr,
+            shape,
+            stride,
+            num_elm,
+        })
+    }
+
+    /// `try_slice_mut`と同じだが、エラーの場合はpanicする。
+    #[inline]
+    pub fn slice_mut(&mut self, index: TensorIndex) -> TensorBase<P::ViewMut<'_>, E> {
+        self.try_slice_mut(index)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// shapeへbroadcastしたviewを返す。
+    /// broadcastで伸ばされる軸のstrideは0になるため、データはコピーされない。
+    #[inline]
+    pub fn broadcast_to(&self, shape: Shape) -> Result<TensorBase<P::View<'_>, E>, BroadcastError> {
+        let stride = broadcast_update_stride(&self.shape, &self.stride, &shape)?;
+        let ptr = self.ptr.to_view(0);
+        let num_elm = self.num_elm;
+        Ok(TensorBase {
+            ptr,
+            shape,
+            stride,
+            num_elm,
+        })
+    }
+
+    /// axesの順番に軸を並べ替えたviewを返す。shapeとstrideを入れ替えるだけでデータはコピーされない。
+    #[inline]
+    pub fn try_permute(&self, axes: &[usize]) -> Result<TensorBase<P::View<'_>, E>, TensorError> {
+        let (shape, stride) = try_permute_update_shape_stride(&self.shape, &self.stride, axes)?;
+        let ptr = self.ptr.to_view(0);
+        let num_elm = self.num_elm;
+        Ok(TensorBase {
             ptr,
             shape,
             stride,
             num_elm,
+        })
+    }
+
+    #[inline]
+    pub fn permute(&self, axes: &[usize]) -> TensorBase<P::View<'_>, E> {
+        self.try_permute(axes).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// 最後の2つの軸を入れ替えたviewを返す。1次元の場合は並べ替えない。
+    #[inline]
+    pub fn t(&self) -> TensorBase<P::View<'_>, E> {
+        self.permute(&transpose_axes(self.shape.num_dim()))
+    }
+
+    /// `t`と同じ
+    #[inline]
+    pub fn transpose(&self) -> TensorBase<P::View<'_>, E> {
+        self.t()
+    }
+
+    /// 同じメモリを指し、shapeとstrideだけを置き換えたviewを返す。
+    #[inline]
+    fn to_view_with(&self, shape: Shape, stride: Stride) -> TensorBase<P::View<'_>, E> {
+        TensorBase {
+            ptr: self.ptr.to_view(0),
+            shape,
+            stride,
+            num_elm: self.num_elm,
         }
     }
 
+    /// 大きさ1の軸axisを取り除いたviewを返す。
+    #[inline]
+    pub fn try_squeeze(&self, axis: usize) -> Result<TensorBase<P::View<'_>, E>, TensorError> {
+        let (shape, stride) = squeeze_update_shape_stride(&self.shape, &self.stride, axis)?;
+        Ok(self.to_view_with(shape, stride))
+    }
+
+    #[inline]
+    pub fn squeeze(&self, axis: usize) -> TensorBase<P::View<'_>, E> {
+        self.try_squeeze(axis).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// axisの位置に大きさ1の軸を挿入したviewを返す。axisは0からnum_dimまで指定できる。
+    #[inline]
+    pub fn try_unsqueeze(&self, axis: usize) -> Result<TensorBase<P::View<'_>, E>, TensorError> {
+        let (shape, stride) = unsqueeze_update_shape_stride(&self.shape, &self.stride, axis)?;
+        Ok(self.to_view_with(shape, stride))
+    }
+
+    #[inline]
+    pub fn unsqueeze(&self, axis: usize) -> TensorBase<P::View<'_>, E> {
+        self.try_unsqueeze(axis).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// `start`から`end`まで(`end`を含む)の軸を1つにまとめたviewを返す。
+    /// strideで表せない場合はエラーになるので、コピーしてもよい場合は`reshape`を使うこと。
+    #[inline]
+    pub fn try_flatten(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> Result<TensorBase<P::View<'_>, E>, TensorError> {
+        let (shape, stride) = flatten_update_shape_stride(&self.shape, &self.stride, start, end)?;
+        Ok(self.to_view_with(shape, stride))
+    }
+
+    #[inline]
+    pub fn flatten(&self, start: usize, end: usize) -> TensorBase<P::View<'_>, E> {
+        self.try_flatten(start, end)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// 軸axisを`sizes`の大きさの軸に分けたviewを返す。
+    #[inline]
+    pub fn try_unflatten(
+        &self,
+        axis: usize,
+        sizes: &[isize],
+    ) -> Result<TensorBase<P::View<'_>, E>, TensorError> {
+        let (shape, stride) =
+            unflatten_update_shape_stride(&self.shape, &self.stride, axis, sizes)?;
+        Ok(self.to_view_with(shape, stride))
+    }
+
+    #[inline]
+    pub fn unflatten(&self, axis: usize, sizes: &[isize]) -> TensorBase<P::View<'_>, E> {
+        self.try_unflatten(axis, sizes)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// 大きさ1の軸をshapeの大きさに伸ばしたviewを返す。shapeの-1は元の大きさのままにする。
+    #[inline]
+    pub fn try_expand(&self, shape: Shape) -> Result<TensorBase<P::View<'_>, E>, TensorError> {
+        let (shape, stride) = expand_update_shape_stride(&self.shape, &self.stride, &shape)?;
+        Ok(self.to_view_with(shape, stride))
+    }
+
+    #[inline]
+    pub fn expand(&self, shape: Shape) -> TensorBase<P::View<'_>, E> {
+        self.try_expand(shape).unwrap_or_else(|e| panic!("{}", e))
+    }
+
     // #[inline]
     // pub fn to_slice(&'_ self) -> &'_ [E]
     // where
@@ -104,3 +271,295 @@ where
     //     self.ptr.to_slice_mut()
     // }
 }
+
+macro_rules! impl_owned_cpu {
+    ($name:ident) => {
+        impl<E: Copy> $name<E> {
+            /// strideがdefaultの場合はviewを、そうでない場合はdefaultのstrideでコピーしたtensorを返す。
+            #[inline]
+            pub fn contiguous(&self) -> CpuCowTensor<'_, E> {
+                cpu_contiguous(self.to_view())
+            }
+
+            /// shapeを変更する。今のstrideで表せる場合はviewを、表せない場合はコピーしたtensorを返す。
+            /// どちらになったかは`CpuCowTensor::is_view`で確認できる。
+            #[inline]
+            pub fn try_reshape(&self, shape: Shape) -> Result<CpuCowTensor<'_, E>, TensorError> {
+                cpu_reshape(self.to_view(), shape)
+            }
+
+            #[inline]
+            pub fn reshape(&self, shape: Shape) -> CpuCowTensor<'_, E> {
+                self.try_reshape(shape).unwrap_or_else(|e| panic!("{}", e))
+            }
+        }
+    };
+}
+
+impl_owned_cpu!(CpuTensor);
+impl_owned_cpu!(CpuArcTensor);
+
+impl<E: Copy> CpuArcTensor<E> {
+    /// 他のtensorとメモリを共有しているかどうか
+    #[inline]
+    pub fn is_shared(&self) -> bool {
+        self.ptr.is_shared()
+    }
+
+    /// `CpuTensor`に変換する。メモリを共有していなければコピーしない。
+    pub fn into_owned(self) -> CpuTensor<E> {
+        TensorBase {
+            ptr: OwnedCpu::from_vec(self.ptr.into_vec()),
+            shape: self.shape,
+            stride: self.stride,
+            num_elm: self.num_elm,
+        }
+    }
+}
+
+/// メモリをコピーせずに共有できるtensorに変換する。
+impl<E: Copy> From<CpuTensor<E>> for CpuArcTensor<E> {
+    fn from(a: CpuTensor<E>) -> Self {
+        TensorBase {
+            ptr: ArcCpu::from_vec(a.ptr.into_vec()),
+            shape: a.shape,
+            stride: a.stride,
+            num_elm: a.num_elm,
+        }
+    }
+}
+
+#[test]
+fn permute_test() {
+    let a = CpuTensor::from_vec((0..24).collect(), Shape::new(vec![2, 3, 4]));
+    let p = a.permute(&[2, 0, 1]);
+    assert_eq!(p.shape(), Shape::new(vec![4, 2, 3]));
+    let v = p.iter().copied().take(6).collect::<Vec<i32>>();
+    assert_eq!(v, vec![0, 4, 8, 12, 16, 20]);
+}
+
+#[test]
+fn transpose_test() {
+    let a = CpuTensor::from_vec((0..6).collect(), Shape::new(vec![2, 3]));
+    let t = a.t();
+    assert_eq!(t.shape(), Shape::new(vec![3, 2]));
+    assert_eq!(t.into_owned().to_vec(), vec![0, 3, 1, 4, 2, 5]);
+    let t = a.transpose().t();
+    assert_eq!(t.shape(), Shape::new(vec![2, 3]));
+    assert_eq!(t.into_owned().to_vec(), vec![0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn contiguous_test() {
+    let a = CpuTensor::from_vec((0..6).collect(), Shape::new(vec![2, 3]));
+    let c = a.contiguous();
+    assert!(c.is_view());
+    let c = a.t().contiguous();
+    assert!(!c.is_view());
+    assert_eq!(
+        c.to_view().stride(),
+        Shape::new(vec![3, 2]).default_stride()
+    );
+    assert_eq!(c.into_owned().to_vec(), vec![0, 3, 1, 4, 2, 5]);
+}
+
+#[test]
+fn contiguous_view_with_offset() {
+    use crate::index;
+    let a = CpuTensor::from_vec((0..9).collect(), Shape::new(vec![3, 3]));
+    let c = a.slice(index![1..3, ..]).contiguous();
+    assert!(c.is_view());
+    assert_eq!(c.into_owned().to_vec(), vec![3, 4, 5, 6, 7, 8]);
+    assert_eq!(
+        a.slice(index![1..3, ..]).into_owned().to_vec(),
+        vec![3, 4, 5, 6, 7, 8]
+    );
+}
+
+#[test]
+fn try_slice_error() {
+    use crate::index;
+    let a = CpuTensor::from_vec((0..9).collect::<Vec<i32>>(), Shape::new(vec![3, 3]));
+    assert_eq!(
+        a.try_slice(index![.., 3]).err(),
+        Some(TensorError::IndexOutOfBounds {
+            axis: 1,
+            index: 3,
+            dim: 3
+        })
+    );
+    assert_eq!(
+        a.try_slice(index![1]).err(),
+        Some(TensorError::NumDimMismatch {
+            expected: 2,
+            got: 1
+        })
+    );
+    assert!(a.try_slice(index![1..3, 1]).is_ok());
+}
+
+#[test]
+fn try_slice_empty() {
+    use crate::index;
+    let mut a = CpuTensor::from_vec(vec![0, 1, 2], Shape::new(vec![3]));
+    assert_eq!(a.slice(index![3..]).shape(), Shape::new(vec![0]));
+    assert_eq!(a.slice_mut(index![3..]).shape(), Shape::new(vec![0]));
+    // 要素を確保していないtensorもsliceできる
+    let mut e = CpuTensor::<i32>::from_vec(vec![], Shape::new(vec![0, 3]));
+    assert_eq!(e.slice(index![.., 1..]).shape(), Shape::new(vec![0, 2]));
+    assert!(e.slice_mut(index![.., ..]).to_vec().is_empty());
+}
+
+#[test]
+fn empty_tensor_test() {
+    let a = CpuTensor::<f32>::zeros(Shape::new(vec![0, 3]));
+    assert_eq!(a.t().shape(), Shape::new(vec![3, 0]));
+    assert_eq!(
+        a.try_permute(&[1, 0]).unwrap().shape(),
+        Shape::new(vec![3, 0])
+    );
+    assert_eq!(
+        a.try_reshape(Shape::new(vec![3, 0])).unwrap().shape(),
+        Shape::new(vec![3, 0])
+    );
+    assert!(a.t().contiguous().into_owned().to_vec().is_empty());
+    assert_eq!(a.unsqueeze(0).shape(), Shape::new(vec![1, 0, 3]));
+    assert_eq!(a.flatten(0, 1).shape(), Shape::new(vec![0]));
+    let b = CpuTensor::<f32>::zeros(Shape::new(vec![0, 1]));
+    assert_eq!(b.squeeze(1).shape(), Shape::new(vec![0]));
+    assert_eq!(
+        b.expand(Shape::new(vec![0, 4])).shape(),
+        Shape::new(vec![0, 4])
+    );
+    let c = b.broadcast_to(Shape::new(vec![0, 3])).unwrap().into_owned();
+    assert_eq!(c.shape(), Shape::new(vec![0, 3]));
+    let v = a.to_view().t().reshape(Shape::new(vec![0, 3]));
+    assert_eq!(v.shape(), Shape::new(vec![0, 3]));
+    let mut d = CpuArcTensor::from(a);
+    assert_eq!(d.to_view_mut().shape(), Shape::new(vec![0, 3]));
+}
+
+#[test]
+fn try_permute_error() {
+    let a = CpuTensor::from_vec((0..6).collect::<Vec<i32>>(), Shape::new(vec![2, 3]));
+    assert_eq!(
+        a.try_permute(&[0, 0]).err(),
+        Some(TensorError::DuplicateAxis { axis: 0 })
+    );
+    assert_eq!(
+        a.try_permute(&[0, 2]).err(),
+        Some(TensorError::InvalidAxis {
+            axis: 2,
+            num_dim: 2
+        })
+    );
+}
+
+#[test]
+fn reshape_view_test() {
+    let a = CpuTensor::from_vec((0..24).collect(), Shape::new(vec![2, 3, 4]));
+    let r = a.reshape(Shape::new(vec![6, 4]));
+    assert!(r.is_view());
+    assert_eq!(r.into_owned().to_vec(), (0..24).collect::<Vec<i32>>());
+}
+
+#[test]
+fn reshape_after_swap_axis() {
+    let mut a = CpuTensor::from_vec((0..6).collect(), Shape::new(vec![2, 3]));
+    a.swap_axis(0, 1);
+    let r = a.reshape(Shape::new(vec![6]));
+    assert!(!r.is_view());
+    assert_eq!(r.into_owned().to_vec(), vec![0, 3, 1, 4, 2, 5]);
+    let r = a.reshape(Shape::new(vec![3, 1, 2]));
+    assert!(r.is_view());
+    assert_eq!(r.into_owned().to_vec(), vec![0, 3, 1, 4, 2, 5]);
+}
+
+#[test]
+fn reshape_sliced_view() {
+    use crate::index;
+    let a = CpuTensor::from_vec((0..24).collect(), Shape::new(vec![4, 6]));
+    let v = a.slice(index![1..3, ..]);
+    let r = v.reshape(Shape::new(vec![3, 4]));
+    assert!(r.is_view());
+    assert_eq!(r.into_owned().to_vec(), (6..18).collect::<Vec<i32>>());
+    let v = a.slice(index![.., 1..3]);
+    let r = v.reshape(Shape::new(vec![8]));
+    assert!(!r.is_view());
+    assert_eq!(r.into_owned().to_vec(), vec![1, 2, 7, 8, 13, 14, 19, 20]);
+}
+
+#[test]
+fn try_reshape_error() {
+    let a = CpuTensor::from_vec((0..6).collect::<Vec<i32>>(), Shape::new(vec![2, 3]));
+    assert_eq!(
+        a.try_reshape(Shape::new(vec![4, 2]))
+            .err()
+            .map(|e| e.to_string()),
+        Some("shape Shape([4, 2]) has 8 elements, but got 6 elements".to_string())
+    );
+}
+
+#[test]
+fn squeeze_unsqueeze_test() {
+    let a = CpuTensor::from_vec((0..6).collect::<Vec<i32>>(), Shape::new(vec![2, 3]));
+    let b = a.unsqueeze(0);
+    assert_eq!(b.shape(), Shape::new(vec![1, 2, 3]));
+    let c = b.squeeze(0);
+    assert_eq!(c.shape(), Shape::new(vec![2, 3]));
+    assert!(a.try_squeeze(1).is_err());
+    assert_eq!(a.unsqueeze(2).shape(), Shape::new(vec![2, 3, 1]));
+}
+
+#[test]
+fn flatten_unflatten_view_test() {
+    let a = CpuTensor::from_vec((0..24).collect::<Vec<i32>>(), Shape::new(vec![2, 3, 4]));
+    let f = a.flatten(0, 1);
+    assert_eq!(f.shape(), Shape::new(vec![6, 4]));
+    let u = f.unflatten(1, &[2, 2]);
+    assert_eq!(u.shape(), Shape::new(vec![6, 2, 2]));
+    assert_eq!(u.into_owned().to_vec(), (0..24).collect::<Vec<i32>>());
+    assert!(a.permute(&[2, 1, 0]).try_flatten(0, 2).is_err());
+    let f = a.permute(&[2, 1, 0]).flatten(0, 0);
+    assert_eq!(f.shape(), Shape::new(vec![4, 3, 2]));
+}
+
+#[test]
+fn expand_view_test() {
+    let a = CpuTensor::from_vec(vec![1, 2, 3], Shape::new(vec![3, 1]));
+    let e = a.expand(Shape::new(vec![-1, 2]));
+    assert_eq!(e.shape(), Shape::new(vec![3, 2]));
+    assert_eq!(e.into_owned().to_vec(), vec![1, 1, 2, 2, 3, 3]);
+    let e = a.to_view().expand(Shape::new(vec![2, 3, 2]));
+    assert_eq!(
+        e.iter().copied().collect::<Vec<i32>>(),
+        vec![1, 1, 2, 2, 3, 3, 1, 1, 2, 2, 3, 3]
+    );
+}
+
+#[test]
+fn arc_tensor_clone_test() {
+    use crate::index;
+    let a = CpuArcTensor::from(CpuTensor::from_vec(
+        (0..6).collect::<Vec<i32>>(),
+        Shape::new(vec![2, 3]),
+    ));
+    let mut b = a.clone();
+    assert!(a.is_shared());
+    assert_eq!(a.as_ptr(), b.as_ptr());
+    assert_eq!(
+        b.t().iter().copied().collect::<Vec<_>>(),
+        vec![0, 3, 1, 4, 2, 5]
+    );
+    // 書き込むとbだけがコピーされる
+    for x in b.slice_mut(index![1, ..]).iter_mut() {
+        *x *= 10;
+    }
+    assert!(!a.is_shared());
+    assert_eq!(a.to_vec(), vec![0, 1, 2, 3, 4, 5]);
+    assert_eq!(b.to_vec(), vec![0, 1, 2, 30, 40, 50]);
+    let ptr = b.as_ptr();
+    let b = b.into_owned();
+    assert_eq!(b.as_ptr(), ptr);
+    assert!(a.reshape(Shape::new(vec![3, 2])).is_view());
+}
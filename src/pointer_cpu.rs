@@ -1,34 +1,44 @@
+use std::marker::PhantomData;
 use std::ptr::NonNull;
+use std::sync::Arc;
 
-use crate::pointer_traits::{Cpu, Mut, Owned, TensorPointer, View, ViewMut};
+use memmap2::{Mmap, MmapMut};
+
+use crate::error::TensorError;
+use crate::memory_pool;
+use crate::mmap::MmapMode;
+use crate::pointer_traits::{Cpu, CpuMut, Mut, Owned, TensorPointer, View, ViewMut};
 
 macro_rules! impl_view {
-    ( $name:ident, $view:ident, $owned: ident, $lt:tt ) => {
-        impl<$lt: Copy> View<$view<$lt>, $owned<$lt>> for $name<$lt> {
+    ( $name:ident, $ref_lt:lifetime ) => {
+        impl<'a, E: Copy> View<OwnedCpu<E>> for $name<'a, E> {
+            type Ref<'b>
+                = ViewCpu<$ref_lt, E>
+            where
+                Self: 'b;
+
             #[inline]
-            fn access_by_offset_region(&self, offset: usize, region: usize) -> $view<$lt> {
-                if self.is_inbound((offset + region - 1) as isize) {
+            fn access_by_offset_region(&self, offset: usize, region: usize) -> Self::Ref<'_> {
+                if offset + region <= self.len() {
                     let offset = self.offset + offset;
                     let len = offset + region;
-                    let cap = self.cap;
-                    let ptr = self.ptr.clone();
-                    $view::from_nonnull(ptr, offset, len, cap)
+                    ViewCpu::from_nonnull(self.ptr, offset, len).unwrap_or_else(|e| panic!("{}", e))
                 } else {
                     panic!("internal error, `access_by_offset_region` out of bounds");
                 }
             }
 
-            fn to_owned(&self) -> $owned<$lt> {
+            fn to_owned(&self) -> OwnedCpu<E> {
                 let v = self.to_vec();
-                $owned::from_vec(v)
+                OwnedCpu::from_vec(v)
             }
         }
     };
 }
 
 macro_rules! impl_mut {
-    ( $name:ident, $lt:tt ) => {
-        impl<$lt: Copy> Mut for $name<$lt> {
+    ( $name:ident $(, $lt:lifetime)? ) => {
+        impl<$($lt,)? E: Copy> Mut for $name<$($lt,)? E> {
             #[inline]
             fn assign_region<P>(&mut self, other: &P, offset: usize, region: usize)
             where
@@ -50,16 +60,27 @@ macro_rules! impl_mut {
 }
 
 macro_rules! impl_cpu {
-    ( $name:ident, $lt:tt) => {
-        impl<$lt: Copy> Cpu for $name<$lt> {
+    ( $name:ident $(, $lt:lifetime)? ) => {
+        impl<$($lt,)? E: Copy> Cpu for $name<$($lt,)? E> {
             #[inline]
-            fn to_slice<'a>(&'a self) -> &'a [<Self as TensorPointer>::Elem] {
+            fn to_slice(&'_ self) -> &'_ [<Self as TensorPointer>::Elem] {
                 unsafe { std::slice::from_raw_parts(self.as_ptr(), self.len()) }
             }
         }
     };
 }
 
+macro_rules! impl_cpu_mut {
+    ( $name:ident $(, $lt:lifetime)? ) => {
+        impl<$($lt,)? E: Copy> CpuMut for $name<$($lt,)? E> {
+            #[inline]
+            fn to_slice_mut(&'_ mut self) -> &'_ mut [<Self as TensorPointer>::Elem] {
+                unsafe { std::slice::from_raw_parts_mut(self.as_ptr().cast_mut(), self.len()) }
+            }
+        }
+    };
+}
+
 /// CPUにデータを確保されたポインタ。データの所有権を持っている。
 #[repr(C)]
 pub struct OwnedCpu<E> {
@@ -69,28 +90,17 @@ pub struct OwnedCpu<E> {
     len: usize,
     /// Vecのcapに当たるもの
     cap: usize,
+    /// `memory_pool`の使用量に記録されているか
+    pooled: bool,
 }
 
 impl<E: Copy> TensorPointer for OwnedCpu<E> {
     type Elem = E;
 
-    #[allow(clippy::redundant_clone)]
     fn to_vec(&self) -> Vec<Self::Elem> {
-        let v = unsafe { Vec::from_raw_parts(self.ptr.as_ptr(), self.len, self.cap) };
-        let res = v.clone();
-        std::mem::forget(v);
-        res
-    }
-
-    fn from_vec(vec: Vec<Self::Elem>) -> Self {
-        let mut vec = vec;
-        let (ptr, len, cap) = (
-            NonNull::new(vec.as_mut_ptr()).expect("Failed to get Pointer for Vec"),
-            vec.len(),
-            vec.capacity(),
-        );
-        std::mem::forget(vec);
-        Self { ptr, len, cap }
+        let mut v = memory_pool::alloc_vec(self.len);
+        v.extend_from_slice(self.to_slice());
+        v
     }
 
     #[inline]
@@ -116,27 +126,46 @@ impl<E: Copy> TensorPointer for OwnedCpu<E> {
 }
 
 impl<E: Copy> Owned for OwnedCpu<E> {
-    type View = ViewCpu<E>;
-    type ViewMut = ViewMutCpu<E>;
-    fn to_view(&self, offset: usize) -> Self::View {
-        if self.is_inbound(offset.try_into().unwrap()) {
-            ViewCpu::from_nonnull(self.ptr, offset, self.len, self.cap)
-        } else {
-            panic!("cannot create view of tensor");
+    type View<'a>
+        = ViewCpu<'a, E>
+    where
+        Self: 'a;
+    type ViewMut<'a>
+        = ViewMutCpu<'a, E>
+    where
+        Self: 'a;
+
+    fn from_vec(vec: Vec<Self::Elem>) -> Self {
+        let mut vec = vec;
+        let (ptr, len, cap) = (
+            NonNull::new(vec.as_mut_ptr()).expect("Failed to get Pointer for Vec"),
+            vec.len(),
+            vec.capacity(),
+        );
+        let pooled = memory_pool::register(&vec);
+        std::mem::forget(vec);
+        Self {
+            ptr,
+            len,
+            cap,
+            pooled,
         }
     }
 
-    fn to_view_mut(&mut self, offset: usize) -> Self::ViewMut {
-        if self.is_inbound(offset.try_into().unwrap()) {
-            ViewMutCpu::from_nonnull(self.ptr, offset, self.len, self.cap)
-        } else {
-            panic!("cannot create view of tensor");
-        }
+    fn to_view(&self, offset: usize) -> Self::View<'_> {
+        ViewCpu::from_nonnull(self.ptr, offset, self.len)
+            .unwrap_or_else(|e| panic!("cannot create view of tensor: {}", e))
+    }
+
+    fn to_view_mut(&mut self, offset: usize) -> Self::ViewMut<'_> {
+        ViewMutCpu::from_nonnull(self.ptr, offset, self.len)
+            .unwrap_or_else(|e| panic!("cannot create view of tensor: {}", e))
     }
 }
 
-impl_mut!(OwnedCpu, E);
-impl_cpu!(OwnedCpu, E);
+impl_mut!(OwnedCpu);
+impl_cpu!(OwnedCpu);
+impl_cpu_mut!(OwnedCpu);
 
 impl<E: Copy> Clone for OwnedCpu<E> {
     fn clone(&self) -> Self {
@@ -147,54 +176,224 @@ impl<E: Copy> Clone for OwnedCpu<E> {
 
 impl<E> Drop for OwnedCpu<E> {
     fn drop(&mut self) {
-        let _ = unsafe { Vec::from_raw_parts(self.ptr.as_ptr(), self.len, self.cap) };
+        let v = unsafe { Vec::from_raw_parts(self.ptr.as_ptr(), self.len, self.cap) };
+        if self.pooled {
+            memory_pool::unregister(&v);
+        }
+        memory_pool::release(v);
     }
 }
 
 impl<E: Copy> OwnedCpu<E> {
+    /// 確保しているメモリをコピーせずにVecとして取り出す。
+    pub(crate) fn into_vec(self) -> Vec<E> {
+        let this = std::mem::ManuallyDrop::new(self);
+        let v = unsafe { Vec::from_raw_parts(this.ptr.as_ptr(), this.len, this.cap) };
+        if this.pooled {
+            memory_pool::unregister(&v);
+        }
+        v
+    }
+
     #[inline]
     pub fn to_slice_mut(&'_ mut self) -> &'_ mut [<Self as TensorPointer>::Elem] {
         unsafe { std::slice::from_raw_parts_mut(self.as_ptr().cast_mut(), self.len) }
     }
 }
 
+/// 参照カウントで複数のtensorから共有されるCPUのメモリ。
+/// cloneはメモリをコピーせずにO(1)で行い、書き込む前に共有されていればコピーする(copy on write)。
+pub struct ArcCpu<E> {
+    data: Arc<Vec<E>>,
+}
+
+impl<E: Copy> ArcCpu<E> {
+    #[inline]
+    fn nonnull(&self) -> NonNull<E> {
+        NonNull::new(self.data.as_ptr() as *mut E).expect("Failed to get Pointer for Vec")
+    }
+
+    /// 他と共有していればコピーして、書き込めるVecを返す
+    #[inline]
+    fn make_mut(&mut self) -> &mut Vec<E> {
+        Arc::make_mut(&mut self.data)
+    }
+
+    /// 他の`ArcCpu`とメモリを共有しているかどうか
+    #[inline]
+    pub(crate) fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.data) > 1
+    }
+
+    /// 共有していなければコピーせずに、共有していればコピーしてVecを取り出す。
+    pub(crate) fn into_vec(self) -> Vec<E> {
+        Arc::try_unwrap(self.data).unwrap_or_else(|data| data.to_vec())
+    }
+}
+
+impl<E: Copy> TensorPointer for ArcCpu<E> {
+    type Elem = E;
+
+    fn to_vec(&self) -> Vec<Self::Elem> {
+        self.data.to_vec()
+    }
+
+    #[inline]
+    fn offset(&self, offset: isize) -> NonNull<Self::Elem> {
+        if !self.is_inbound(offset) {
+            panic!("offset is out of bound");
+        }
+        unsafe { NonNull::new_unchecked(self.data.as_ptr().offset(offset) as *mut E) }
+    }
+
+    #[inline]
+    fn as_ptr(&self) -> *const Self::Elem {
+        self.data.as_ptr()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    #[inline]
+    fn offset_num(&self) -> usize {
+        0
+    }
+}
+
+impl<E: Copy> Owned for ArcCpu<E> {
+    type View<'a>
+        = ViewCpu<'a, E>
+    where
+        Self: 'a;
+    type ViewMut<'a>
+        = ViewMutCpu<'a, E>
+    where
+        Self: 'a;
+
+    fn from_vec(vec: Vec<Self::Elem>) -> Self {
+        Self {
+            data: Arc::new(vec),
+        }
+    }
+
+    /// メモリをコピーせずに共有する
+    fn clone_mem_layout(&self) -> Self {
+        self.clone()
+    }
+
+    fn to_view(&self, offset: usize) -> Self::View<'_> {
+        ViewCpu::from_nonnull(self.nonnull(), offset, self.len())
+            .unwrap_or_else(|e| panic!("cannot create view of tensor: {}", e))
+    }
+
+    /// 共有している場合は、viewを作る前にコピーする
+    fn to_view_mut(&mut self, offset: usize) -> Self::ViewMut<'_> {
+        self.make_mut();
+        ViewMutCpu::from_nonnull(self.nonnull(), offset, self.len())
+            .unwrap_or_else(|e| panic!("cannot create view of tensor: {}", e))
+    }
+}
+
+impl<E: Copy> Mut for ArcCpu<E> {
+    #[inline]
+    fn assign_region<P>(&mut self, other: &P, offset: usize, region: usize)
+    where
+        P: TensorPointer<Elem = <Self as TensorPointer>::Elem>,
+    {
+        if !self.is_inbound((offset + region - 1) as isize) {
+            panic!("this is out of bound");
+        }
+        let src = unsafe { std::slice::from_raw_parts(other.as_ptr(), region) };
+        self.make_mut()[offset..offset + region].copy_from_slice(src);
+    }
+}
+
+impl<E: Copy> Cpu for ArcCpu<E> {
+    #[inline]
+    fn to_slice(&'_ self) -> &'_ [<Self as TensorPointer>::Elem] {
+        &self.data
+    }
+}
+
+impl<E: Copy> CpuMut for ArcCpu<E> {
+    /// 共有している場合は、sliceを返す前にコピーする
+    #[inline]
+    fn to_slice_mut(&'_ mut self) -> &'_ mut [<Self as TensorPointer>::Elem] {
+        self.make_mut()
+    }
+}
+
+impl<E> Clone for ArcCpu<E> {
+    /// メモリはコピーせず、参照カウントを増やすだけ
+    fn clone(&self) -> Self {
+        Self {
+            data: Arc::clone(&self.data),
+        }
+    }
+}
+
+/// `OwnedCpu`などのメモリを借用するポインタ。`'a`の間だけ有効。
 #[repr(C)]
-pub struct ViewCpu<E> {
+pub struct ViewCpu<'a, E> {
     ptr: NonNull<E>,
     offset: usize,
     len: usize,
-    cap: usize,
+    _marker: PhantomData<&'a E>,
 }
 
-impl<E> ViewCpu<E> {
+impl<'a, E> ViewCpu<'a, E> {
     #[inline]
-    fn from_nonnull(ptr: NonNull<E>, offset: usize, len: usize, cap: usize) -> ViewCpu<E> {
-        if offset >= len {
-            panic!("must offset < len");
+    fn from_nonnull(ptr: NonNull<E>, offset: usize, len: usize) -> Result<Self, TensorError> {
+        // 要素を確保していないメモリのviewは、先頭を指すものだけ作れる
+        if offset >= len && !(offset == 0 && len == 0) {
+            return Err(TensorError::OffsetOutOfBounds { offset, len });
         }
-        Self {
+        Ok(Self {
             ptr,
             offset,
             len,
-            cap,
+            _marker: PhantomData,
+        })
+    }
+
+    /// 外部で確保されたメモリを借用するviewを作る。
+    /// ptrからlen個の要素が、`'a`の間有効で変更されないことを呼び出し側が保証しなければならない。
+    #[inline]
+    pub(crate) unsafe fn from_raw_parts(
+        ptr: NonNull<E>,
+        offset: usize,
+        len: usize,
+    ) -> Result<Self, TensorError> {
+        Self::from_nonnull(ptr, offset, len)
+    }
+
+    /// 同じメモリを、確保された先頭から数えて`offset`の位置から指すviewを返す。
+    #[inline]
+    pub(crate) fn with_offset(&self, offset: usize) -> Result<Self, TensorError> {
+        Self::from_nonnull(self.ptr, offset, self.len)
+    }
+}
+
+impl<'a, E: Copy> Clone for ViewCpu<'a, E> {
+    fn clone(&self) -> Self {
+        Self {
+            ptr: self.ptr,
+            offset: self.offset,
+            len: self.len,
+            _marker: PhantomData,
         }
     }
 }
 
-impl<E: Copy> TensorPointer for ViewCpu<E> {
+impl<'a, E: Copy> TensorPointer for ViewCpu<'a, E> {
     type Elem = E;
 
-    #[allow(clippy::redundant_clone)]
     fn to_vec(&self) -> Vec<Self::Elem> {
-        let v = unsafe { Vec::from_raw_parts(self.ptr.as_ptr(), self.len, self.cap) };
-        let res = v.clone();
-        std::mem::forget(v);
-        res
-    }
-
-    fn from_vec(vec: Vec<Self::Elem>) -> Self {
-        let owned_cpu = OwnedCpu::from_vec(vec);
-        owned_cpu.to_view(0)
+        let mut v = memory_pool::alloc_vec(self.len);
+        v.extend_from_slice(unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) });
+        v
     }
 
     #[inline]
@@ -202,7 +401,7 @@ impl<E: Copy> TensorPointer for ViewCpu<E> {
         if !self.is_inbound(offset) {
             panic!("offset is out of bound");
         }
-        unsafe { NonNull::new(self.ptr.as_ptr().offset(offset) as *mut Self::Elem).unwrap() }
+        unsafe { NonNull::new(self.ptr.as_ptr().offset(offset)).unwrap() }
     }
 
     #[inline]
@@ -221,46 +420,41 @@ impl<E: Copy> TensorPointer for ViewCpu<E> {
     }
 }
 
-impl_view!(ViewCpu, ViewCpu, OwnedCpu, E);
+impl_view!(ViewCpu, 'a);
 
-impl_cpu!(ViewCpu, E);
+impl_cpu!(ViewCpu, 'a);
 
+/// `OwnedCpu`などのメモリを可変で借用するポインタ。`'a`の間、他からはメモリにアクセスできない。
 #[repr(C)]
-pub struct ViewMutCpu<E> {
+pub struct ViewMutCpu<'a, E> {
     ptr: NonNull<E>,
     offset: usize,
     len: usize,
-    cap: usize,
+    _marker: PhantomData<&'a mut E>,
 }
 
-impl<E> ViewMutCpu<E> {
-    fn from_nonnull(ptr: NonNull<E>, offset: usize, len: usize, cap: usize) -> ViewMutCpu<E> {
-        if offset >= len {
-            panic!("must offset < len");
+impl<'a, E> ViewMutCpu<'a, E> {
+    fn from_nonnull(ptr: NonNull<E>, offset: usize, len: usize) -> Result<Self, TensorError> {
+        // 要素を確保していないメモリのviewは、先頭を指すものだけ作れる
+        if offset >= len && !(offset == 0 && len == 0) {
+            return Err(TensorError::OffsetOutOfBounds { offset, len });
         }
-        Self {
+        Ok(Self {
             ptr,
             offset,
             len,
-            cap,
-        }
+            _marker: PhantomData,
+        })
     }
 }
 
-impl<E: Copy> TensorPointer for ViewMutCpu<E> {
+impl<'a, E: Copy> TensorPointer for ViewMutCpu<'a, E> {
     type Elem = E;
 
-    #[allow(clippy::redundant_clone)]
     fn to_vec(&self) -> Vec<Self::Elem> {
-        let v = unsafe { Vec::from_raw_parts(self.ptr.as_ptr(), self.len, self.cap) };
-        let res = v.clone();
-        std::mem::forget(v);
-        res
-    }
-
-    fn from_vec(vec: Vec<Self::Elem>) -> Self {
-        let mut owned_cpu = OwnedCpu::from_vec(vec);
-        owned_cpu.to_view_mut(0)
+        let mut v = memory_pool::alloc_vec(self.len);
+        v.extend_from_slice(unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) });
+        v
     }
 
     #[inline]
@@ -268,7 +462,7 @@ impl<E: Copy> TensorPointer for ViewMutCpu<E> {
         if !self.is_inbound(offset) {
             panic!("offset is out of bound");
         }
-        unsafe { NonNull::new(self.ptr.as_ptr().offset(offset) as *mut Self::Elem).unwrap() }
+        unsafe { NonNull::new(self.ptr.as_ptr().offset(offset)).unwrap() }
     }
 
     #[inline]
@@ -287,19 +481,173 @@ impl<E: Copy> TensorPointer for ViewMutCpu<E> {
     }
 }
 
-impl_view!(ViewMutCpu, ViewCpu, OwnedCpu, E);
+impl_view!(ViewMutCpu, 'b);
+
+impl_mut!(ViewMutCpu, 'a);
+impl_cpu!(ViewMutCpu, 'a);
+impl_cpu_mut!(ViewMutCpu, 'a);
+
+impl<'a, E: Copy> ViewMut<OwnedCpu<E>> for ViewMutCpu<'a, E> {}
+
+impl<'a, E: Copy> ViewMutCpu<'a, E> {
+    /// 同じメモリを指すポインタを、`&mut self`を借用している間だけ使える形で返す
+    #[inline]
+    pub(crate) fn reborrow(&mut self) -> ViewMutCpu<'_, E> {
+        ViewMutCpu {
+            ptr: self.ptr,
+            offset: self.offset,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn to_slice_mut(&'_ mut self) -> &'_ mut [<Self as TensorPointer>::Elem] {
+        unsafe { std::slice::from_raw_parts_mut(self.as_ptr().cast_mut(), self.len()) }
+    }
+}
+
+enum MmapStorage {
+    ReadOnly(Mmap),
+    CopyOnWrite(MmapMut),
+}
+
+/// メモリマップしたファイルを指すポインタ。ファイルの内容は必要になった時にOSが読み込む。
+/// `CopyOnWrite`の場合、書き込んだページだけがメモリにコピーされ、ファイルは変更されない。
+pub struct MmapCpu<E> {
+    map: MmapStorage,
+    /// mapの先頭から数えた、要素の先頭のbyte数
+    byte_offset: usize,
+    len: usize,
+    _marker: PhantomData<E>,
+}
+
+impl<E> MmapCpu<E> {
+    /// mapの`byte_offset`からlen個の要素を指すポインタを作る。
+    /// 範囲がmapに収まり、alignmentが合っていて、どのbit列も`E`として有効であることを呼び出し側が保証しなければならない。
+    pub(crate) unsafe fn from_mmap(map: Mmap, byte_offset: usize, len: usize) -> Self {
+        Self {
+            map: MmapStorage::ReadOnly(map),
+            byte_offset,
+            len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// `from_mmap`の`CopyOnWrite`版
+    pub(crate) unsafe fn from_mmap_copy(map: MmapMut, byte_offset: usize, len: usize) -> Self {
+        Self {
+            map: MmapStorage::CopyOnWrite(map),
+            byte_offset,
+            len,
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn mode(&self) -> MmapMode {
+        match self.map {
+            MmapStorage::ReadOnly(_) => MmapMode::ReadOnly,
+            MmapStorage::CopyOnWrite(_) => MmapMode::CopyOnWrite,
+        }
+    }
+
+    #[inline]
+    fn nonnull(&self) -> NonNull<E> {
+        let base = match &self.map {
+            MmapStorage::ReadOnly(m) => m.as_ptr(),
+            MmapStorage::CopyOnWrite(m) => m.as_ptr(),
+        };
+        unsafe { NonNull::new_unchecked(base.add(self.byte_offset) as *mut E) }
+    }
+
+    /// `CopyOnWrite`の場合のみ、メモリを可変で借用するポインタを返す
+    pub(crate) fn to_view_mut(&mut self) -> Option<ViewMutCpu<'_, E>> {
+        match &mut self.map {
+            MmapStorage::ReadOnly(_) => None,
+            MmapStorage::CopyOnWrite(m) => {
+                let ptr = unsafe { NonNull::new_unchecked(m.as_mut_ptr().add(self.byte_offset)) };
+                Some(
+                    ViewMutCpu::from_nonnull(ptr.cast(), 0, self.len)
+                        .unwrap_or_else(|e| panic!("cannot create view of tensor: {}", e)),
+                )
+            }
+        }
+    }
+}
+
+impl<E: Copy> TensorPointer for MmapCpu<E> {
+    type Elem = E;
 
-impl_mut!(ViewMutCpu, E);
-impl_cpu!(ViewMutCpu, E);
+    fn to_vec(&self) -> Vec<Self::Elem> {
+        let mut v = memory_pool::alloc_vec(self.len);
+        v.extend_from_slice(self.to_slice());
+        v
+    }
 
-impl<E: Copy> ViewMut<ViewCpu<E>, OwnedCpu<E>> for ViewMutCpu<E> {}
+    #[inline]
+    fn offset(&self, offset: isize) -> NonNull<Self::Elem> {
+        if !self.is_inbound(offset) {
+            panic!("offset is out of bound");
+        }
+        unsafe { NonNull::new_unchecked(self.nonnull().as_ptr().offset(offset)) }
+    }
+
+    #[inline]
+    fn as_ptr(&self) -> *const Self::Elem {
+        self.nonnull().as_ptr()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn offset_num(&self) -> usize {
+        0
+    }
+}
+
+impl<E: Copy> View<OwnedCpu<E>> for MmapCpu<E> {
+    type Ref<'b>
+        = ViewCpu<'b, E>
+    where
+        Self: 'b;
 
-impl<E: Copy> ViewMutCpu<E> {
     #[inline]
-    #[allow(clippy::mut_from_ref, clippy::wrong_self_convention)]
-    pub fn to_slice_mut(&'_ self) -> &'_ mut [<Self as TensorPointer>::Elem] {
-        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len()) }
+    fn access_by_offset_region(&self, offset: usize, region: usize) -> Self::Ref<'_> {
+        if offset + region <= self.len() {
+            ViewCpu::from_nonnull(self.nonnull(), offset, offset + region)
+                .unwrap_or_else(|e| panic!("{}", e))
+        } else {
+            panic!("internal error, `access_by_offset_region` out of bounds");
+        }
     }
+
+    fn to_owned(&self) -> OwnedCpu<E> {
+        OwnedCpu::from_vec(self.to_vec())
+    }
+}
+
+impl_cpu!(MmapCpu);
+
+#[test]
+fn arc_cpu_copy_on_write_test() {
+    let a = ArcCpu::from_vec(vec![0, 1, 2, 3]);
+    let mut b = a.clone();
+    assert!(a.is_shared());
+    assert_eq!(a.as_ptr(), b.as_ptr());
+    b.to_slice_mut()[0] = 10;
+    assert!(!a.is_shared());
+    assert_ne!(a.as_ptr(), b.as_ptr());
+    assert_eq!(a.to_vec(), vec![0, 1, 2, 3]);
+    assert_eq!(b.to_vec(), vec![10, 1, 2, 3]);
+    // 共有していなければコピーしない
+    let ptr = b.as_ptr();
+    b.to_view_mut(1)
+        .assign_region(&OwnedCpu::from_vec(vec![20]), 0, 1);
+    assert_eq!(b.as_ptr(), ptr);
+    assert_eq!(b.into_vec(), vec![10, 20, 2, 3]);
 }
 
 #[test]
@@ -9,8 +9,6 @@ pub trait TensorPointer: Sized {
     }
     /// clone mem and make vec
     fn to_vec(&self) -> Vec<Self::Elem>;
-    /// Create Self from a vector
-    fn from_vec(vec: Vec<Self::Elem>) -> Self;
     /// Pointerで保持されている先頭から、offset分だけoffsetしNonNUllを返す
     fn offset(&self, offset: isize) -> NonNull<Self::Elem>;
     /// Pointerで保持されている先頭のポインタを返す
@@ -23,8 +21,16 @@ pub trait TensorPointer: Sized {
 
 /// データに関してDropする責任があるポインタ
 pub trait Owned: TensorPointer {
-    type View;
-    type ViewMut;
+    /// 所有しているメモリを借用するポインタ。借用している間は`Self`を変更できない。
+    type View<'a>: TensorPointer<Elem = Self::Elem>
+    where
+        Self: 'a;
+    /// 所有しているメモリを可変で借用するポインタ。借用している間は`Self`を使えない。
+    type ViewMut<'a>: TensorPointer<Elem = Self::Elem>
+    where
+        Self: 'a;
+    /// Create Self from a vector
+    fn from_vec(vec: Vec<Self::Elem>) -> Self;
     /// 確保したメモリをそのままクローンする。
     /// (shapeやstrideを考慮せず、メモリのレイアウトそのままクローンする)
     fn clone_mem_layout(&self) -> Self {
@@ -32,9 +38,9 @@ pub trait Owned: TensorPointer {
         Self::from_vec(v)
     }
     /// pointerをviewにキャストする
-    fn to_view(&self, offset: usize) -> Self::View;
+    fn to_view(&self, offset: usize) -> Self::View<'_>;
     /// pointerをview_mutにキャストする
-    fn to_view_mut(&mut self, offset: usize) -> Self::ViewMut;
+    fn to_view_mut(&mut self, offset: usize) -> Self::ViewMut<'_>;
 }
 
 /// データに関して、可変であるポインタ
@@ -45,19 +51,28 @@ pub trait Mut: TensorPointer {
 }
 
 /// データの参照を持つポインタ
-pub trait View<V: View<V, O>, O: Owned>: TensorPointer {
-    // Return がSelfではない理由
-    // 返り値がSelfではない理由はViewMutに実装した際にViewMutではなくViewが返り値になってほしいから
+pub trait View<O: Owned>: TensorPointer {
+    /// `access_by_offset_region`が返すポインタ。
+    /// ViewMutに実装した場合もViewMutではなくViewになり、`&self`を借用している間だけ使える。
+    type Ref<'b>: TensorPointer<Elem = Self::Elem>
+    where
+        Self: 'b;
+
     /// offsetで指定された場所を起点にして、regionで指定された長さを持つViewのポインタを返す
-    fn access_by_offset_region(&self, offset: usize, region: usize) -> V;
+    fn access_by_offset_region(&self, offset: usize, region: usize) -> Self::Ref<'_>;
 
     /// Cast for Pointer wihch impl Owned
     fn to_owned(&self) -> O;
 }
 
-pub trait ViewMut<V: View<V, O>, O: Owned>: View<V, O> + Mut {}
+pub trait ViewMut<O: Owned>: View<O> + Mut {}
 
 /// Impl to Cpu Pointer only
 pub trait Cpu: TensorPointer {
     fn to_slice(&'_ self) -> &'_ [<Self as TensorPointer>::Elem];
 }
+
+/// Impl to Cpu Pointer which can be written
+pub trait CpuMut: Cpu + Mut {
+    fn to_slice_mut(&'_ mut self) -> &'_ mut [<Self as TensorPointer>::Elem];
+}
@@ -0,0 +1,281 @@
+use num_traits::{Float, PrimInt};
+
+use crate::shape::Shape;
+use crate::tensor::CpuTensor;
+
+/// seedから状態を作るためのSplitMix64
+#[inline]
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// 再現性のある乱数生成器 (xoshiro256**)
+/// 同じseedからは、platformや依存crateのversionに関わらず同じ乱数列が生成される。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Generator {
+    s: [u64; 4],
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Self {
+        let mut state = seed;
+        let s = [
+            splitmix64(&mut state),
+            splitmix64(&mut state),
+            splitmix64(&mut state),
+            splitmix64(&mut state),
+        ];
+        Self { s }
+    }
+
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.s[1] << 17;
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+        self.s[2] ^= t;
+        self.s[3] = self.s[3].rotate_left(45);
+        result
+    }
+
+    /// [0, 1)の一様乱数。上位53bitを使う。
+    #[inline]
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// [0, n)の一様な整数。剰余による偏りが出ないよう棄却法を使う。
+    #[inline]
+    fn next_below(&mut self, n: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return x % n;
+            }
+        }
+    }
+
+    /// 標準正規分布に従う乱数。Box-Muller法を使う。
+    #[inline]
+    fn next_standard_normal(&mut self) -> f64 {
+        // ln(0)を避けるため(0, 1]にする
+        let u1 = 1.0 - self.next_f64();
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+
+    /// 標準正規分布を[a, b]に制限した分布に従う乱数。
+    /// 範囲に応じて提案分布を切り替え、範囲が裾の方にあっても受理される確率が一定以上になるようにする。
+    /// (C. P. Robert, "Simulation of truncated normal variables", 1995)
+    fn next_truncated_standard_normal(&mut self, a: f64, b: f64) -> f64 {
+        if b <= 0.0 {
+            return -self.next_truncated_standard_normal(-b, -a);
+        }
+        if a < 0.0 {
+            // 0を含む範囲。狭い場合は一様分布から提案する
+            if b - a < (2.0 * std::f64::consts::PI).sqrt() {
+                loop {
+                    let z = a + (b - a) * self.next_f64();
+                    if self.next_f64() < (-z * z / 2.0).exp() {
+                        return z;
+                    }
+                }
+            }
+            loop {
+                let z = self.next_standard_normal();
+                if a <= z && z <= b {
+                    return z;
+                }
+            }
+        }
+        // 0 <= a。aから始まる指数分布から提案する
+        let lambda = (a + (a * a + 4.0).sqrt()) / 2.0;
+        if (b - a) * lambda < 1.0 {
+            // 指数分布ではbを超えることが多い狭い範囲は、一様分布から提案する
+            loop {
+                let z = a + (b - a) * self.next_f64();
+                if self.next_f64() < ((a * a - z * z) / 2.0).exp() {
+                    return z;
+                }
+            }
+        }
+        loop {
+            let z = a - (1.0 - self.next_f64()).ln() / lambda;
+            if z <= b && self.next_f64() < (-(z - lambda) * (z - lambda) / 2.0).exp() {
+                return z;
+            }
+        }
+    }
+
+    fn sample<E, F>(&mut self, shape: Shape, mut f: F) -> CpuTensor<E>
+    where
+        E: Copy,
+        F: FnMut(&mut Self) -> E,
+    {
+        let v = (0..shape.num_elms()).map(|_| f(self)).collect();
+        CpuTensor::from_vec(v, shape)
+    }
+
+    /// [low, high)の一様分布
+    pub fn uniform<E: Float>(&mut self, shape: Shape, low: E, high: E) -> CpuTensor<E> {
+        assert!(low < high, "uniform requires low < high");
+        self.sample(shape, |g| {
+            let x = low + E::from(g.next_f64()).unwrap() * (high - low);
+            // 丸め誤差でhighになった場合は範囲内に戻す
+            if x < high {
+                x
+            } else {
+                low
+            }
+        })
+    }
+
+    /// 平均mean, 標準偏差stdの正規分布
+    pub fn normal<E: Float>(&mut self, shape: Shape, mean: E, std: E) -> CpuTensor<E> {
+        assert!(std >= E::zero(), "normal requires std >= 0");
+        self.sample(shape, |g| {
+            mean + E::from(g.next_standard_normal()).unwrap() * std
+        })
+    }
+
+    /// 平均mean, 標準偏差stdの正規分布を[low, high]に制限した分布。
+    /// `low`, `high`が平均から大きく離れていても、要素ごとに数回程度の引き直しで済む。
+    pub fn truncated_normal<E: Float>(
+        &mut self,
+        shape: Shape,
+        mean: E,
+        std: E,
+        low: E,
+        high: E,
+    ) -> CpuTensor<E> {
+        assert!(
+            std > E::zero() && low < high,
+            "truncated_normal requires std > 0 and low < high"
+        );
+        let standardize = |x: E| ((x - mean) / std).to_f64().unwrap();
+        let (a, b) = (standardize(low), standardize(high));
+        self.sample(shape, |g| {
+            let x = mean + E::from(g.next_truncated_standard_normal(a, b)).unwrap() * std;
+            // 丸め誤差で範囲外になった場合は範囲内に戻す
+            x.max(low).min(high)
+        })
+    }
+
+    /// 確率pで1, 1 - pで0になるbernoulli分布
+    pub fn bernoulli<E: Float>(&mut self, shape: Shape, p: f64) -> CpuTensor<E> {
+        assert!((0.0..=1.0).contains(&p), "bernoulli requires 0 <= p <= 1");
+        self.sample(shape, |g| {
+            if g.next_f64() < p {
+                E::one()
+            } else {
+                E::zero()
+            }
+        })
+    }
+
+    /// [low, high)の一様な整数
+    pub fn randint<I: PrimInt>(&mut self, shape: Shape, low: I, high: I) -> CpuTensor<I> {
+        assert!(low < high, "randint requires low < high");
+        let low_i128 = low.to_i128().unwrap();
+        let range = (high.to_i128().unwrap() - low_i128) as u64;
+        self.sample(shape, |g| {
+            I::from(low_i128 + g.next_below(range) as i128).unwrap()
+        })
+    }
+
+    /// 0からn - 1までの整数をランダムに並べ替えた1次元のtensor (Fisher-Yates shuffle)
+    pub fn randperm(&mut self, n: usize) -> CpuTensor<usize> {
+        let mut v = (0..n).collect::<Vec<usize>>();
+        for i in (1..n).rev() {
+            let j = self.next_below(i as u64 + 1) as usize;
+            v.swap(i, j);
+        }
+        CpuTensor::from_vec(v, Shape::new(vec![n as isize]))
+    }
+}
+
+#[test]
+fn generator_golden_test() {
+    let mut g = Generator::new(0);
+    // seedが同じなら、どの環境でも同じ値になる
+    assert_eq!(g.next_u64(), 0x99ec_5f36_cb75_f2b4);
+    assert_eq!(g.next_u64(), 0xbf6e_1f78_4956_452a);
+}
+
+#[test]
+fn reproducible_test() {
+    let shape = Shape::new(vec![3, 4]);
+    let a = Generator::new(42).normal(shape.clone(), 0f32, 1.);
+    let b = Generator::new(42).normal(shape.clone(), 0f32, 1.);
+    assert_eq!(a.to_vec(), b.to_vec());
+    let c = Generator::new(43).normal(shape, 0f32, 1.);
+    assert_ne!(a.to_vec(), c.to_vec());
+}
+
+#[test]
+fn uniform_range_test() {
+    let mut g = Generator::new(1);
+    let a = g.uniform(Shape::new(vec![1000]), -2f64, 3.);
+    assert!(a.iter().all(|x| (-2. ..3.).contains(x)));
+    let mean = a.iter().sum::<f64>() / 1000.;
+    assert!((mean - 0.5).abs() < 0.2);
+}
+
+#[test]
+fn normal_moments_test() {
+    let mut g = Generator::new(2);
+    let a = g.normal(Shape::new(vec![10000]), 1f64, 2.);
+    let mean = a.iter().sum::<f64>() / 10000.;
+    let var = a.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / 10000.;
+    assert!((mean - 1.).abs() < 0.1);
+    assert!((var.sqrt() - 2.).abs() < 0.1);
+}
+
+#[test]
+fn truncated_normal_test() {
+    let mut g = Generator::new(3);
+    let a = g.truncated_normal(Shape::new(vec![1000]), 0f32, 1., -0.5, 0.5);
+    assert!(a.iter().all(|x| (-0.5..=0.5).contains(x)));
+}
+
+#[test]
+fn truncated_normal_tail_test() {
+    let mut g = Generator::new(3);
+    let a = g.truncated_normal(Shape::new(vec![1000]), 0f64, 1., 5., 6.);
+    assert!(a.iter().all(|x| (5.0..=6.0).contains(x)));
+    // 裾では下端の近くに集まる
+    let mean = a.iter().sum::<f64>() / 1000.;
+    assert!((5.1..5.3).contains(&mean));
+    let a = g.truncated_normal(Shape::new(vec![1000]), 0f32, 1., -41., -40.);
+    assert!(a.iter().all(|x| (-41.0..=-40.0).contains(x)));
+    let a = g.truncated_normal(Shape::new(vec![100]), 1f64, 2., 1.5, 1.5001);
+    assert!(a.iter().all(|x| (1.5..=1.5001).contains(x)));
+}
+
+#[test]
+fn bernoulli_test() {
+    let mut g = Generator::new(4);
+    let a = g.bernoulli::<f32>(Shape::new(vec![1000]), 0.3);
+    assert!(a.iter().all(|x| *x == 0. || *x == 1.));
+    let ones = a.iter().filter(|x| **x == 1.).count();
+    assert!((200..400).contains(&ones));
+}
+
+#[test]
+fn randint_randperm_test() {
+    let mut g = Generator::new(5);
+    let a = g.randint(Shape::new(vec![100]), -3i64, 3);
+    assert!(a.iter().all(|x| (-3..3).contains(x)));
+    let p = g.randperm(10);
+    let mut v = p.to_vec();
+    v.sort_unstable();
+    assert_eq!(v, (0..10).collect::<Vec<usize>>());
+}
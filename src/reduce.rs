@@ -0,0 +1,435 @@
+use std::iter::Take;
+use std::ops::RangeFull;
+
+use num_traits::{Float, Num};
+
+use crate::error::TensorError;
+use crate::iter::Iter;
+use crate::pointer_traits::{Cpu, TensorPointer};
+use crate::shape::{Shape, Stride};
+use crate::tensor::{CpuTensor, TensorBase};
+
+/// reductionを行う軸の指定
+/// `usize`で1つの軸、`&[usize]`や`Vec<usize>`で複数の軸、`..`で全ての軸を表す。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReduceAxes {
+    All,
+    Axes(Vec<usize>),
+}
+
+impl From<usize> for ReduceAxes {
+    fn from(axis: usize) -> Self {
+        ReduceAxes::Axes(vec![axis])
+    }
+}
+
+impl From<&[usize]> for ReduceAxes {
+    fn from(axes: &[usize]) -> Self {
+        ReduceAxes::Axes(axes.to_vec())
+    }
+}
+
+impl<const N: usize> From<[usize; N]> for ReduceAxes {
+    fn from(axes: [usize; N]) -> Self {
+        ReduceAxes::Axes(axes.to_vec())
+    }
+}
+
+impl From<Vec<usize>> for ReduceAxes {
+    fn from(axes: Vec<usize>) -> Self {
+        ReduceAxes::Axes(axes)
+    }
+}
+
+impl From<RangeFull> for ReduceAxes {
+    fn from(_: RangeFull) -> Self {
+        ReduceAxes::All
+    }
+}
+
+impl ReduceAxes {
+    /// 軸の番号を昇順に並べて返す。範囲外や重複がある場合はエラーを返す。
+    fn to_sorted_vec(&self, num_dim: usize) -> Result<Vec<usize>, TensorError> {
+        let mut axes = match self {
+            ReduceAxes::All => (0..num_dim).collect(),
+            ReduceAxes::Axes(axes) => axes.clone(),
+        };
+        axes.sort_unstable();
+        for (i, axis) in axes.iter().enumerate() {
+            if *axis >= num_dim {
+                return Err(TensorError::InvalidAxis {
+                    axis: *axis,
+                    num_dim,
+                });
+            }
+            if i > 0 && axes[i - 1] == *axis {
+                return Err(TensorError::DuplicateAxis { axis: *axis });
+            }
+        }
+        Ok(axes)
+    }
+}
+
+type Group<'a, 'b, E> = Take<&'b mut Iter<'a, E>>;
+
+/// 残す軸を前に、reductionする軸を後ろに並べ替えて要素を辿り、
+/// reductionする軸の要素数ごとにまとめて`f`に渡す。
+fn reduce<P, E, T, F>(
+    a: &TensorBase<P, E>,
+    axes: ReduceAxes,
+    keepdims: bool,
+    f: F,
+) -> Result<CpuTensor<T>, TensorError>
+where
+    P: TensorPointer<Elem = E> + Cpu,
+    T: Copy,
+    F: Fn(Group<'_, '_, E>) -> T,
+{
+    let num_dim = a.shape.num_dim();
+    let axes = axes.to_sorted_vec(num_dim)?;
+    let kept = (0..num_dim)
+        .filter(|x| !axes.contains(x))
+        .collect::<Vec<usize>>();
+
+    let order = kept.iter().chain(axes.iter());
+    let shape = Shape::new(order.clone().map(|x| a.shape[*x]).collect());
+    let stride = Stride::new(order.map(|x| a.stride[*x]).collect());
+    let group = axes.iter().map(|x| a.shape[*x] as usize).product::<usize>();
+    let num_out = kept.iter().map(|x| a.shape[*x] as usize).product::<usize>();
+
+    let mut iter = a.strided_iter(shape, stride);
+    let v = (0..num_out)
+        .map(|_| f(iter.by_ref().take(group)))
+        .collect::<Vec<T>>();
+
+    let out_shape = if keepdims {
+        (0..num_dim)
+            .map(|x| if axes.contains(&x) { 1 } else { a.shape[x] })
+            .collect::<Vec<isize>>()
+    } else if kept.is_empty() {
+        vec![1]
+    } else {
+        kept.iter().map(|x| a.shape[*x]).collect::<Vec<isize>>()
+    };
+    TensorBase::try_from_vec(v, Shape::new(out_shape))
+}
+
+#[inline]
+fn is_nan<E: PartialOrd>(x: &E) -> bool {
+    x.partial_cmp(x).is_none()
+}
+
+/// `better`で選ばれる要素のgroup内での位置を返す。同じ値の場合は最初の位置を返し、NaNは最優先される。
+fn arg_select<E, F>(group: Group<'_, '_, E>, better: F) -> usize
+where
+    E: PartialOrd + Copy,
+    F: Fn(&E, &E) -> bool,
+{
+    let mut group = group.enumerate();
+    let (mut idx, mut acc) = match group.next() {
+        Some((i, x)) => (i, *x),
+        None => panic!("attempt to get argmax or argmin of an empty sequence"),
+    };
+    for (i, x) in group {
+        if is_nan(&acc) {
+            break;
+        }
+        if is_nan(x) || better(x, &acc) {
+            idx = i;
+            acc = *x;
+        }
+    }
+    idx
+}
+
+/// `better`で選ばれる要素を返す。NaNが含まれる場合はNaNを返す。
+fn select<E, F>(group: Group<'_, '_, E>, better: F) -> E
+where
+    E: PartialOrd + Copy,
+    F: Fn(&E, &E) -> bool,
+{
+    let mut group = group.copied();
+    let mut acc = match group.next() {
+        Some(x) => x,
+        None => panic!("zero-size array to reduction operation which has no identity"),
+    };
+    for x in group {
+        if is_nan(&acc) {
+            break;
+        }
+        if is_nan(&x) || better(&x, &acc) {
+            acc = x;
+        }
+    }
+    acc
+}
+
+impl<P, E> TensorBase<P, E>
+where
+    P: TensorPointer<Elem = E> + Cpu,
+    E: Copy,
+{
+    /// 指定した軸の要素の和を計算する。
+    /// `keepdims`がtrueの場合、reductionした軸は大きさ1の軸として残る。
+    /// 全ての軸をreductionし`keepdims`がfalseの場合、shapeは`[1]`になる。
+    /// 軸が範囲外の場合や重複している場合はエラーを返す。
+    pub fn try_sum<A: Into<ReduceAxes>>(
+        &self,
+        axes: A,
+        keepdims: bool,
+    ) -> Result<CpuTensor<E>, TensorError>
+    where
+        E: Num,
+    {
+        reduce(self, axes.into(), keepdims, |group| {
+            group.fold(E::zero(), |acc, x| acc + *x)
+        })
+    }
+
+    /// `try_sum`と同じだが、エラーの場合はpanicする。
+    pub fn sum<A: Into<ReduceAxes>>(&self, axes: A, keepdims: bool) -> CpuTensor<E>
+    where
+        E: Num,
+    {
+        self.try_sum(axes, keepdims)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// 指定した軸の要素の積を計算する。
+    pub fn try_prod<A: Into<ReduceAxes>>(
+        &self,
+        axes: A,
+        keepdims: bool,
+    ) -> Result<CpuTensor<E>, TensorError>
+    where
+        E: Num,
+    {
+        reduce(self, axes.into(), keepdims, |group| {
+            group.fold(E::one(), |acc, x| acc * *x)
+        })
+    }
+
+    /// `try_prod`と同じだが、エラーの場合はpanicする。
+    pub fn prod<A: Into<ReduceAxes>>(&self, axes: A, keepdims: bool) -> CpuTensor<E>
+    where
+        E: Num,
+    {
+        self.try_prod(axes, keepdims)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// 指定した軸の要素の平均を計算する。
+    pub fn try_mean<A: Into<ReduceAxes>>(
+        &self,
+        axes: A,
+        keepdims: bool,
+    ) -> Result<CpuTensor<E>, TensorError>
+    where
+        E: Float,
+    {
+        reduce(self, axes.into(), keepdims, |group| {
+            let n = group.len();
+            let sum = group.fold(E::zero(), |acc, x| acc + *x);
+            sum / E::from(n).unwrap()
+        })
+    }
+
+    /// `try_mean`と同じだが、エラーの場合はpanicする。
+    pub fn mean<A: Into<ReduceAxes>>(&self, axes: A, keepdims: bool) -> CpuTensor<E>
+    where
+        E: Float,
+    {
+        self.try_mean(axes, keepdims)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// 指定した軸の最大値を計算する。NaNが含まれる場合はNaNになる。
+    pub fn try_max<A: Into<ReduceAxes>>(
+        &self,
+        axes: A,
+        keepdims: bool,
+    ) -> Result<CpuTensor<E>, TensorError>
+    where
+        E: PartialOrd,
+    {
+        reduce(self, axes.into(), keepdims, |group| {
+            select(group, |x, acc| x > acc)
+        })
+    }
+
+    /// `try_max`と同じだが、エラーの場合はpanicする。
+    pub fn max<A: Into<ReduceAxes>>(&self, axes: A, keepdims: bool) -> CpuTensor<E>
+    where
+        E: PartialOrd,
+    {
+        self.try_max(axes, keepdims)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// 指定した軸の最小値を計算する。NaNが含まれる場合はNaNになる。
+    pub fn try_min<A: Into<ReduceAxes>>(
+        &self,
+        axes: A,
+        keepdims: bool,
+    ) -> Result<CpuTensor<E>, TensorError>
+    where
+        E: PartialOrd,
+    {
+        reduce(self, axes.into(), keepdims, |group| {
+            select(group, |x, acc| x < acc)
+        })
+    }
+
+    /// `try_min`と同じだが、エラーの場合はpanicする。
+    pub fn min<A: Into<ReduceAxes>>(&self, axes: A, keepdims: bool) -> CpuTensor<E>
+    where
+        E: PartialOrd,
+    {
+        self.try_min(axes, keepdims)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// 指定した軸で最大値を持つ要素の位置を返す。
+    /// 複数の軸を指定した場合は、それらの軸をrow majorで平坦化した時の位置を返す。
+    pub fn try_argmax<A: Into<ReduceAxes>>(
+        &self,
+        axes: A,
+        keepdims: bool,
+    ) -> Result<CpuTensor<usize>, TensorError>
+    where
+        E: PartialOrd,
+    {
+        reduce(self, axes.into(), keepdims, |group| {
+            arg_select(group, |x, acc| x > acc)
+        })
+    }
+
+    /// `try_argmax`と同じだが、エラーの場合はpanicする。
+    pub fn argmax<A: Into<ReduceAxes>>(&self, axes: A, keepdims: bool) -> CpuTensor<usize>
+    where
+        E: PartialOrd,
+    {
+        self.try_argmax(axes, keepdims)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// 指定した軸で最小値を持つ要素の位置を返す。
+    /// 複数の軸を指定した場合は、それらの軸をrow majorで平坦化した時の位置を返す。
+    pub fn try_argmin<A: Into<ReduceAxes>>(
+        &self,
+        axes: A,
+        keepdims: bool,
+    ) -> Result<CpuTensor<usize>, TensorError>
+    where
+        E: PartialOrd,
+    {
+        reduce(self, axes.into(), keepdims, |group| {
+            arg_select(group, |x, acc| x < acc)
+        })
+    }
+
+    /// `try_argmin`と同じだが、エラーの場合はpanicする。
+    pub fn argmin<A: Into<ReduceAxes>>(&self, axes: A, keepdims: bool) -> CpuTensor<usize>
+    where
+        E: PartialOrd,
+    {
+        self.try_argmin(axes, keepdims)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+#[test]
+fn sum_axis_test() {
+    let a = CpuTensor::from_vec((0..6).collect(), Shape::new(vec![2, 3]));
+    let s0 = a.sum(0, false);
+    assert_eq!(s0.shape(), Shape::new(vec![3]));
+    assert_eq!(s0.to_vec(), vec![3, 5, 7]);
+    let s1 = a.sum(1, true);
+    assert_eq!(s1.shape(), Shape::new(vec![2, 1]));
+    assert_eq!(s1.to_vec(), vec![3, 12]);
+}
+
+#[test]
+fn sum_all_test() {
+    let a = CpuTensor::from_vec((0..24).collect(), Shape::new(vec![2, 3, 4]));
+    let s = a.sum(.., false);
+    assert_eq!(s.shape(), Shape::new(vec![1]));
+    assert_eq!(s.to_vec(), vec![276]);
+    let s = a.sum(.., true);
+    assert_eq!(s.shape(), Shape::new(vec![1, 1, 1]));
+}
+
+#[test]
+fn sum_axes_test() {
+    let a = CpuTensor::from_vec((0..24).collect(), Shape::new(vec![2, 3, 4]));
+    let s = a.sum([2, 0], false);
+    assert_eq!(s.shape(), Shape::new(vec![3]));
+    assert_eq!(s.to_vec(), vec![60, 92, 124]);
+}
+
+#[test]
+fn sum_non_contiguous_view() {
+    use crate::index;
+    let a = CpuTensor::from_vec((0..125).collect(), Shape::new(vec![5, 5, 5]));
+    let v = a.slice(index![.., 2, ..]);
+    let s = v.sum(0, false);
+    assert_eq!(s.to_vec(), vec![300, 305, 310, 315, 320]);
+    let s = v.sum(1, false);
+    assert_eq!(s.to_vec(), vec![60, 185, 310, 435, 560]);
+}
+
+#[test]
+fn prod_mean_test() {
+    let a = CpuTensor::from_vec(vec![1., 2., 3., 4.], Shape::new(vec![2, 2]));
+    assert_eq!(a.prod(1, false).to_vec(), vec![2., 12.]);
+    assert_eq!(a.mean(0, false).to_vec(), vec![2., 3.]);
+    assert_eq!(a.mean(.., false).to_vec(), vec![2.5]);
+}
+
+#[test]
+fn max_min_test() {
+    let a = CpuTensor::from_vec(vec![3, 1, 4, 1, 5, 9], Shape::new(vec![2, 3]));
+    assert_eq!(a.max(1, false).to_vec(), vec![4, 9]);
+    assert_eq!(a.min(0, false).to_vec(), vec![1, 1, 4]);
+    assert_eq!(a.max(.., false).to_vec(), vec![9]);
+}
+
+#[test]
+fn max_nan_test() {
+    let a = CpuTensor::from_vec(vec![1., f64::NAN, 3.], Shape::new(vec![3]));
+    assert!(a.max(0, false).to_vec()[0].is_nan());
+    assert_eq!(a.argmin(0, false).to_vec(), vec![1]);
+}
+
+#[test]
+fn argmax_argmin_test() {
+    let a = CpuTensor::from_vec(vec![3, 7, 4, 8, 5, 8], Shape::new(vec![2, 3]));
+    assert_eq!(a.argmax(1, false).to_vec(), vec![1, 0]);
+    assert_eq!(a.argmin(0, true).to_vec(), vec![0, 1, 0]);
+    assert_eq!(a.argmin(0, true).shape(), Shape::new(vec![1, 3]));
+    assert_eq!(a.argmax(.., false).to_vec(), vec![3]);
+}
+
+#[test]
+fn try_reduce_error() {
+    let a = CpuTensor::from_vec((0..6).collect::<Vec<i32>>(), Shape::new(vec![2, 3]));
+    assert_eq!(
+        a.try_sum(2, false).err(),
+        Some(TensorError::InvalidAxis {
+            axis: 2,
+            num_dim: 2
+        })
+    );
+    assert_eq!(
+        a.try_max([1, 1], false).err(),
+        Some(TensorError::DuplicateAxis { axis: 1 })
+    );
+    assert_eq!(a.try_argmin(.., false).unwrap().to_vec(), vec![0]);
+}
+
+#[test]
+#[should_panic]
+fn reduce_axis_out_of_bounds() {
+    let a = CpuTensor::from_vec((0..6).collect::<Vec<i32>>(), Shape::new(vec![2, 3]));
+    let _ = a.sum(2, false);
+}
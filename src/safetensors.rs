@@ -0,0 +1,426 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use std::ptr::NonNull;
+
+use ::safetensors::tensor::{Metadata, TensorInfo};
+use ::safetensors::{serialize, serialize_to_file, Dtype, SafeTensorError, SafeTensors, View};
+use half::{bf16, f16};
+use memmap2::Mmap;
+use thiserror::Error;
+
+use crate::dyn_tensor::DynTensor;
+use crate::error::TensorError;
+use crate::npy::ElementBytes;
+use crate::pointer_cpu::ViewCpu;
+use crate::pointer_traits::{Cpu, TensorPointer};
+use crate::shape::Shape;
+use crate::tensor::{CpuTensor, CpuViewTensor, TensorBase};
+
+/// safetensorsの読み書きで起こるエラー
+#[derive(Error, Debug)]
+pub enum SafeTensorsError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Format(#[from] SafeTensorError),
+
+    #[error(transparent)]
+    Tensor(#[from] TensorError),
+
+    #[error("tensor {name} not found")]
+    NotFound { name: String },
+
+    #[error("dtype mismatch: tensor {name} has {got:?}, but expected {expected:?}")]
+    DtypeMismatch {
+        name: String,
+        expected: Dtype,
+        got: Dtype,
+    },
+
+    #[error("tensor {name} has unsupported dtype {dtype:?}")]
+    UnsupportedDtype { name: String, dtype: Dtype },
+
+    #[error("tensor {name} cannot be viewed without copying: {reason}")]
+    CannotView { name: String, reason: &'static str },
+}
+
+/// safetensorsに保存できる要素の型。`ElementBytes`と同じくcrateの外からは実装できない。
+pub trait SafeTensorsElement: ElementBytes {
+    const DTYPE: Dtype;
+}
+
+impl SafeTensorsElement for f32 {
+    const DTYPE: Dtype = Dtype::F32;
+}
+impl SafeTensorsElement for f64 {
+    const DTYPE: Dtype = Dtype::F64;
+}
+impl SafeTensorsElement for i8 {
+    const DTYPE: Dtype = Dtype::I8;
+}
+impl SafeTensorsElement for i16 {
+    const DTYPE: Dtype = Dtype::I16;
+}
+impl SafeTensorsElement for i32 {
+    const DTYPE: Dtype = Dtype::I32;
+}
+impl SafeTensorsElement for i64 {
+    const DTYPE: Dtype = Dtype::I64;
+}
+impl SafeTensorsElement for u8 {
+    const DTYPE: Dtype = Dtype::U8;
+}
+impl SafeTensorsElement for bool {
+    const DTYPE: Dtype = Dtype::BOOL;
+}
+impl SafeTensorsElement for f16 {
+    const DTYPE: Dtype = Dtype::F16;
+}
+impl SafeTensorsElement for bf16 {
+    const DTYPE: Dtype = Dtype::BF16;
+}
+
+enum Storage {
+    Mmap(Mmap),
+    Bytes(Vec<u8>),
+}
+
+impl Storage {
+    fn bytes(&self) -> &[u8] {
+        match self {
+            Storage::Mmap(m) => m,
+            Storage::Bytes(b) => b,
+        }
+    }
+}
+
+/// safetensorsのheaderを読み、名前を指定してtensorを取り出す。
+/// `open`はファイルをmemory mapするため、実際に読み込むのはアクセスしたtensorの部分だけになる。
+pub struct SafeTensorsReader {
+    storage: Storage,
+    /// データ部分の先頭位置(headerの長さ + 8)
+    data_start: usize,
+    metadata: Metadata,
+    /// 名前からtensorの情報を引くための表。`new`で一度だけ作る。
+    tensors: HashMap<String, TensorInfo>,
+}
+
+impl SafeTensorsReader {
+    /// ファイルをmemory mapして開く。
+    ///
+    /// # Safety
+    /// 返り値と、そこから作ったviewを使っている間、ファイルが他のプロセスなどから
+    /// 変更されたり切り詰められたりしてはいけない。変更された場合の動作は未定義になる。
+    /// 安全に読む場合は`from_bytes`を使う。
+    pub unsafe fn open<T: AsRef<Path>>(path: T) -> Result<Self, SafeTensorsError> {
+        let file = File::open(path)?;
+        let mmap = Mmap::map(&file)?;
+        Self::new(Storage::Mmap(mmap))
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, SafeTensorsError> {
+        Self::new(Storage::Bytes(bytes))
+    }
+
+    fn new(storage: Storage) -> Result<Self, SafeTensorsError> {
+        let (n, metadata) = SafeTensors::read_metadata(storage.bytes())?;
+        let tensors = metadata
+            .tensors()
+            .into_iter()
+            .map(|(name, info)| (name, info.clone()))
+            .collect();
+        Ok(Self {
+            storage,
+            data_start: n + 8,
+            metadata,
+            tensors,
+        })
+    }
+
+    /// 含まれているtensorの名前
+    pub fn names(&self) -> Vec<String> {
+        self.tensors.keys().cloned().collect()
+    }
+
+    /// header の`__metadata__`
+    pub fn metadata(&self) -> Option<&HashMap<String, String>> {
+        self.metadata.metadata().as_ref()
+    }
+
+    pub fn info(&self, name: &str) -> Result<&TensorInfo, SafeTensorsError> {
+        self.tensors
+            .get(name)
+            .ok_or_else(|| SafeTensorsError::NotFound {
+                name: name.to_string(),
+            })
+    }
+
+    /// tensorのshape, データ部分を確認して返す。
+    fn tensor_bytes<E: SafeTensorsElement>(
+        &self,
+        name: &str,
+    ) -> Result<(Shape, &[u8]), SafeTensorsError> {
+        let info = self.info(name)?;
+        if info.dtype != E::DTYPE {
+            return Err(SafeTensorsError::DtypeMismatch {
+                name: name.to_string(),
+                expected: E::DTYPE,
+                got: info.dtype,
+            });
+        }
+        let shape = Shape::new(info.shape.iter().map(|d| *d as isize).collect());
+        shape.validate()?;
+        let (start, end) = info.data_offsets;
+        let bytes = &self.storage.bytes()[self.data_start + start..self.data_start + end];
+        Ok((shape, bytes))
+    }
+
+    /// tensorを`OwnedCpu`にコピーして読み込む。
+    pub fn read<E: SafeTensorsElement>(
+        &self,
+        name: &str,
+    ) -> Result<CpuTensor<E>, SafeTensorsError> {
+        let (shape, bytes) = self.tensor_bytes::<E>(name)?;
+        let v = bytes.chunks_exact(E::SIZE).map(E::from_le_bytes).collect();
+        Ok(CpuTensor::try_from_vec(v, shape)?)
+    }
+
+    /// tensorを、要素の型をファイルのdtypeに合わせて`OwnedCpu`にコピーして読み込む。
+    pub fn read_dyn(&self, name: &str) -> Result<DynTensor, SafeTensorsError> {
+        let dtype = self.info(name)?.dtype;
+        Ok(match dtype {
+            Dtype::F16 => self.read::<f16>(name)?.into(),
+            Dtype::BF16 => self.read::<bf16>(name)?.into(),
+            Dtype::F32 => self.read::<f32>(name)?.into(),
+            Dtype::F64 => self.read::<f64>(name)?.into(),
+            Dtype::I8 => self.read::<i8>(name)?.into(),
+            Dtype::I16 => self.read::<i16>(name)?.into(),
+            Dtype::I32 => self.read::<i32>(name)?.into(),
+            Dtype::I64 => self.read::<i64>(name)?.into(),
+            Dtype::U8 => self.read::<u8>(name)?.into(),
+            Dtype::BOOL => self.read::<bool>(name)?.into(),
+            dtype => {
+                return Err(SafeTensorsError::UnsupportedDtype {
+                    name: name.to_string(),
+                    dtype,
+                })
+            }
+        })
+    }
+
+    /// コピーせずに、ファイルのデータをそのまま指すviewを返す。
+    /// データのalignmentが合わない場合やbig endianの環境では、エラーになるので`read`を使う。
+    /// 返り値はこの`SafeTensorsReader`を借用している。
+    pub fn view<E: SafeTensorsElement>(
+        &self,
+        name: &str,
+    ) -> Result<CpuViewTensor<'_, E>, SafeTensorsError> {
+        let (shape, bytes) = self.tensor_bytes::<E>(name)?;
+        let cannot_view = |reason| SafeTensorsError::CannotView {
+            name: name.to_string(),
+            reason,
+        };
+        if cfg!(target_endian = "big") {
+            return Err(cannot_view("data is little endian"));
+        }
+        if bytes.as_ptr().align_offset(std::mem::align_of::<E>()) != 0 {
+            return Err(cannot_view("data is not aligned"));
+        }
+        if E::DTYPE == Dtype::BOOL && bytes.iter().any(|b| *b > 1) {
+            return Err(cannot_view("bool data contains values other than 0 and 1"));
+        }
+        let len = bytes.len() / E::SIZE;
+        let ptr = NonNull::new(bytes.as_ptr() as *mut E).unwrap();
+        let ptr = unsafe { ViewCpu::from_raw_parts(ptr, 0, len)? };
+        let stride = shape.default_stride();
+        Ok(TensorBase {
+            ptr,
+            shape,
+            stride,
+            num_elm: len,
+        })
+    }
+}
+
+/// 書き込むtensorの型を消すためのtrait
+trait SafeTensorsSource {
+    fn dtype(&self) -> Dtype;
+    fn data(&self) -> Cow<'_, [u8]>;
+}
+
+impl<P, E> SafeTensorsSource for TensorBase<P, E>
+where
+    P: TensorPointer<Elem = E> + Cpu,
+    E: SafeTensorsElement,
+{
+    fn dtype(&self) -> Dtype {
+        E::DTYPE
+    }
+
+    fn data(&self) -> Cow<'_, [u8]> {
+        let num_elms = self.shape.num_elms();
+        if cfg!(target_endian = "little") && self.shape.is_default_stride(&self.stride) {
+            // 連続したデータはそのまま書き込む
+            let ptr = self.ptr.as_ptr() as *const u8;
+            Cow::Borrowed(unsafe { std::slice::from_raw_parts(ptr, num_elms * E::SIZE) })
+        } else {
+            let mut bytes = Vec::with_capacity(num_elms * E::SIZE);
+            for x in self.iter() {
+                x.write_le_bytes(&mut bytes);
+            }
+            Cow::Owned(bytes)
+        }
+    }
+}
+
+struct Entry<'a> {
+    tensor: &'a dyn SafeTensorsSource,
+    shape: Vec<usize>,
+}
+
+impl<'a> View for &Entry<'a> {
+    fn dtype(&self) -> Dtype {
+        self.tensor.dtype()
+    }
+
+    fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    fn data(&self) -> Cow<'_, [u8]> {
+        self.tensor.data()
+    }
+
+    fn data_len(&self) -> usize {
+        self.shape.iter().product::<usize>() * self.tensor.dtype().size()
+    }
+}
+
+/// 名前を付けたtensorをsafetensorsの形式で書き込む。型の異なるtensorを混ぜて書き込める。
+/// viewも連続したデータとして書き込む。
+#[derive(Default)]
+pub struct SafeTensorsWriter<'a> {
+    tensors: Vec<(String, Entry<'a>)>,
+    metadata: Option<HashMap<String, String>>,
+}
+
+impl<'a> SafeTensorsWriter<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add<P, E>(&mut self, name: &str, a: &'a TensorBase<P, E>)
+    where
+        P: TensorPointer<Elem = E> + Cpu,
+        E: SafeTensorsElement,
+    {
+        let shape = a.shape.iter().map(|d| *d as usize).collect();
+        self.tensors
+            .push((name.to_string(), Entry { tensor: a, shape }));
+    }
+
+    /// headerの`__metadata__`に書き込む値を設定する。
+    pub fn set_metadata(&mut self, metadata: HashMap<String, String>) {
+        self.metadata = Some(metadata);
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SafeTensorsError> {
+        let data = self.tensors.iter().map(|(name, e)| (name.as_str(), e));
+        Ok(serialize(data, &self.metadata)?)
+    }
+
+    pub fn write<T: AsRef<Path>>(&self, path: T) -> Result<(), SafeTensorsError> {
+        let data = self.tensors.iter().map(|(name, e)| (name.as_str(), e));
+        Ok(serialize_to_file(data, &self.metadata, path.as_ref())?)
+    }
+}
+
+#[cfg(test)]
+use crate::index;
+
+#[test]
+fn safetensors_round_trip_test() {
+    let a = CpuTensor::from_vec(vec![1f32, 2., 3., 4., 5., 6.], Shape::new(vec![2, 3]));
+    let b = CpuTensor::from_vec((0..6).collect::<Vec<i64>>(), Shape::new(vec![3, 2]));
+    let b = b.slice(index![.., ..;-1]);
+    let mut writer = SafeTensorsWriter::new();
+    writer.add("a", &a);
+    writer.add("b", &b);
+    writer.set_metadata(HashMap::from([("format".to_string(), "pt".to_string())]));
+    let bytes = writer.to_bytes().unwrap();
+
+    let reader = SafeTensorsReader::from_bytes(bytes).unwrap();
+    let mut names = reader.names();
+    names.sort();
+    assert_eq!(names, vec!["a", "b"]);
+    assert_eq!(reader.metadata().unwrap()["format"], "pt");
+    assert_eq!(reader.info("a").unwrap().shape, vec![2, 3]);
+    let ra = reader.read::<f32>("a").unwrap();
+    assert_eq!(ra.shape(), a.shape());
+    assert_eq!(ra.to_vec(), a.to_vec());
+    let rb = reader.read::<i64>("b").unwrap();
+    assert_eq!(rb.to_vec(), vec![1, 0, 3, 2, 5, 4]);
+    assert!(matches!(
+        reader.read::<f64>("a"),
+        Err(SafeTensorsError::DtypeMismatch { .. })
+    ));
+    assert!(matches!(
+        reader.read::<f32>("c"),
+        Err(SafeTensorsError::NotFound { .. })
+    ));
+}
+
+#[test]
+fn safetensors_mmap_view_test() {
+    let path = std::env::temp_dir().join(format!(
+        "safetensors_mmap_test_{}.safetensors",
+        std::process::id()
+    ));
+    let a = CpuTensor::from_vec((0..12).map(|x| x as f64).collect(), Shape::new(vec![3, 4]));
+    let mask = CpuTensor::from_vec(vec![true, false], Shape::new(vec![2]));
+    let mut writer = SafeTensorsWriter::new();
+    writer.add("weight", &a);
+    writer.add("mask", &mask);
+    writer.write(&path).unwrap();
+
+    let reader = unsafe { SafeTensorsReader::open(&path) }.unwrap();
+    let v = reader.view::<f64>("weight").unwrap();
+    assert_eq!(v.shape(), Shape::new(vec![3, 4]));
+    assert_eq!(
+        v.t().iter().copied().collect::<Vec<_>>(),
+        vec![0., 4., 8., 1., 5., 9., 2., 6., 10., 3., 7., 11.]
+    );
+    assert_eq!(
+        reader.read::<bool>("mask").unwrap().to_vec(),
+        vec![true, false]
+    );
+    drop(reader);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn safetensors_half_test() {
+    let a = CpuTensor::from_vec(vec![0.5f32, -1., 3.], Shape::new(vec![3]));
+    let h = a.cast::<f16>();
+    let b = a.cast::<bf16>();
+    let mut writer = SafeTensorsWriter::new();
+    writer.add("h", &h);
+    writer.add("b", &b);
+    let reader = SafeTensorsReader::from_bytes(writer.to_bytes().unwrap()).unwrap();
+    assert_eq!(reader.info("h").unwrap().dtype, Dtype::F16);
+    assert_eq!(
+        reader.read_dyn("b").unwrap().dtype(),
+        crate::dyn_tensor::DType::BF16
+    );
+    assert_eq!(
+        reader.read::<f16>("h").unwrap().cast::<f32>().to_vec(),
+        a.to_vec()
+    );
+    assert_eq!(
+        reader.read::<bf16>("b").unwrap().cast::<f32>().to_vec(),
+        a.to_vec()
+    );
+}
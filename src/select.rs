@@ -0,0 +1,380 @@
+use num_traits::PrimInt;
+
+use crate::error::TensorError;
+use crate::iter::StridedOffsets;
+use crate::pointer_traits::{Cpu, CpuMut, TensorPointer};
+use crate::shape::{broadcast_shapes, Shape, Stride};
+use crate::tensor::{CpuTensor, TensorBase};
+
+/// 負のindexを末尾から数えた位置に直し、範囲外であればエラーを返す。
+#[inline]
+fn normalize_index<I: PrimInt>(index: I, axis: usize, dim: isize) -> Result<isize, TensorError> {
+    let index = index.to_isize().unwrap_or(isize::MAX);
+    let normalized = if index < 0 { index + dim } else { index };
+    if normalized < 0 || dim <= normalized {
+        return Err(TensorError::IndexOutOfBounds { axis, index, dim });
+    }
+    Ok(normalized)
+}
+
+#[inline]
+fn check_axis(shape: &Shape, axis: usize) -> Result<(), TensorError> {
+    if axis >= shape.num_dim() {
+        return Err(TensorError::InvalidAxis {
+            axis,
+            num_dim: shape.num_dim(),
+        });
+    }
+    Ok(())
+}
+
+/// `take`で参照する要素のoffsetと、結果のshapeを返す。
+fn take_offsets<P, E, PI, I>(
+    a: &TensorBase<P, E>,
+    indices: &TensorBase<PI, I>,
+    axis: usize,
+) -> Result<(Vec<isize>, Shape), TensorError>
+where
+    P: TensorPointer<Elem = E>,
+    PI: TensorPointer<Elem = I> + Cpu,
+    I: PrimInt,
+{
+    check_axis(&a.shape, axis)?;
+    let dim = a.shape[axis];
+    let indices_offset = indices
+        .iter()
+        .map(|i| normalize_index(*i, axis, dim).map(|i| i * a.stride[axis]))
+        .collect::<Result<Vec<isize>, TensorError>>()?;
+
+    let outer = StridedOffsets::new(
+        Shape::new(a.shape[..axis].to_vec()),
+        Stride::new(a.stride[..axis].to_vec()),
+    );
+    let inner = StridedOffsets::new(
+        Shape::new(a.shape[axis + 1..].to_vec()),
+        Stride::new(a.stride[axis + 1..].to_vec()),
+    )
+    .collect::<Vec<isize>>();
+
+    let mut offsets = Vec::with_capacity(outer.len() * indices_offset.len() * inner.len());
+    for o in outer {
+        for i in indices_offset.iter() {
+            offsets.extend(inner.iter().map(|k| o + i + k));
+        }
+    }
+
+    let shape = a.shape[..axis]
+        .iter()
+        .chain(indices.shape.iter())
+        .chain(a.shape[axis + 1..].iter())
+        .copied()
+        .collect();
+    Ok((offsets, Shape::new(shape)))
+}
+
+/// `gather`で参照する要素のoffsetを返す。
+fn gather_offsets<P, E, PI, I>(
+    a: &TensorBase<P, E>,
+    axis: usize,
+    index: &TensorBase<PI, I>,
+) -> Result<Vec<isize>, TensorError>
+where
+    P: TensorPointer<Elem = E>,
+    PI: TensorPointer<Elem = I> + Cpu,
+    I: PrimInt,
+{
+    check_axis(&a.shape, axis)?;
+    if index.shape.num_dim() != a.shape.num_dim() {
+        return Err(TensorError::NumDimMismatch {
+            expected: a.shape.num_dim(),
+            got: index.shape.num_dim(),
+        });
+    }
+    if index
+        .shape
+        .iter()
+        .zip(a.shape.iter())
+        .enumerate()
+        .any(|(d, (i, s))| d != axis && i > s)
+    {
+        return Err(TensorError::ShapeMismatch {
+            expected: a.shape.clone(),
+            got: index.shape.clone(),
+        });
+    }
+
+    let dim = a.shape[axis];
+    index
+        .indexed_iter()
+        .map(|(pos, i)| {
+            let i = normalize_index(*i, axis, dim)?;
+            Ok(pos
+                .iter()
+                .zip(a.stride.iter())
+                .enumerate()
+                .map(|(d, (p, st))| if d == axis { i * st } else { p * st })
+                .sum())
+        })
+        .collect()
+}
+
+impl<P, E> TensorBase<P, E>
+where
+    P: TensorPointer<Elem = E> + Cpu,
+    E: Copy,
+{
+    #[inline]
+    fn read_offsets(&self, offsets: &[isize], shape: Shape) -> CpuTensor<E> {
+        let ptr = self.ptr.as_ptr();
+        let v = offsets
+            .iter()
+            .map(|offset| unsafe { *ptr.offset(*offset) })
+            .collect();
+        TensorBase::from_vec(v, shape)
+    }
+
+    /// `axis`に沿って`indices`で指定した位置の要素を取り出す。NumPyの`take`と同じ。
+    /// 結果のshapeは`shape[..axis] + indices.shape + shape[axis + 1..]`になる。
+    /// 負のindexは末尾から数える。
+    pub fn try_take<PI, I>(
+        &self,
+        indices: &TensorBase<PI, I>,
+        axis: usize,
+    ) -> Result<CpuTensor<E>, TensorError>
+    where
+        PI: TensorPointer<Elem = I> + Cpu,
+        I: PrimInt,
+    {
+        let (offsets, shape) = take_offsets(self, indices, axis)?;
+        Ok(self.read_offsets(&offsets, shape))
+    }
+
+    pub fn take<PI, I>(&self, indices: &TensorBase<PI, I>, axis: usize) -> CpuTensor<E>
+    where
+        PI: TensorPointer<Elem = I> + Cpu,
+        I: PrimInt,
+    {
+        self.try_take(indices, axis)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// 1次元の`indices`を使う`take`。PyTorchの`index_select`と同じ。
+    pub fn try_index_select<PI, I>(
+        &self,
+        axis: usize,
+        indices: &TensorBase<PI, I>,
+    ) -> Result<CpuTensor<E>, TensorError>
+    where
+        PI: TensorPointer<Elem = I> + Cpu,
+        I: PrimInt,
+    {
+        if indices.shape.num_dim() != 1 {
+            return Err(TensorError::NumDimMismatch {
+                expected: 1,
+                got: indices.shape.num_dim(),
+            });
+        }
+        self.try_take(indices, axis)
+    }
+
+    pub fn index_select<PI, I>(&self, axis: usize, indices: &TensorBase<PI, I>) -> CpuTensor<E>
+    where
+        PI: TensorPointer<Elem = I> + Cpu,
+        I: PrimInt,
+    {
+        self.try_index_select(axis, indices)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// `axis`の位置を`index`の値で置き換えた位置の要素を集める。PyTorchの`gather`と同じ。
+    /// `index`は同じ次元数で、`axis`以外の軸の大きさはこのtensor以下でなければならない。
+    pub fn try_gather<PI, I>(
+        &self,
+        axis: usize,
+        index: &TensorBase<PI, I>,
+    ) -> Result<CpuTensor<E>, TensorError>
+    where
+        PI: TensorPointer<Elem = I> + Cpu,
+        I: PrimInt,
+    {
+        let offsets = gather_offsets(self, axis, index)?;
+        Ok(self.read_offsets(&offsets, index.shape()))
+    }
+
+    pub fn gather<PI, I>(&self, axis: usize, index: &TensorBase<PI, I>) -> CpuTensor<E>
+    where
+        PI: TensorPointer<Elem = I> + Cpu,
+        I: PrimInt,
+    {
+        self.try_gather(axis, index)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// `mask`がtrueの位置の要素を、論理的な順番で1次元に並べて返す。
+    /// `mask`とこのtensorは互いにbroadcastできなければならない。
+    pub fn try_masked_select<PM>(
+        &self,
+        mask: &TensorBase<PM, bool>,
+    ) -> Result<CpuTensor<E>, TensorError>
+    where
+        PM: TensorPointer<Elem = bool> + Cpu,
+    {
+        let shape = broadcast_shapes(&[self.shape(), mask.shape()])?;
+        let v = self
+            .broadcast_iter(&shape)?
+            .zip(mask.broadcast_iter(&shape)?)
+            .filter(|(_, m)| **m)
+            .map(|(x, _)| *x)
+            .collect::<Vec<E>>();
+        let len = v.len() as isize;
+        TensorBase::try_from_vec(v, Shape::new(vec![len]))
+    }
+
+    pub fn masked_select<PM>(&self, mask: &TensorBase<PM, bool>) -> CpuTensor<E>
+    where
+        PM: TensorPointer<Elem = bool> + Cpu,
+    {
+        self.try_masked_select(mask)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl<P, E> TensorBase<P, E>
+where
+    P: TensorPointer<Elem = E> + CpuMut,
+    E: Copy,
+{
+    /// `take`で取り出す位置に`values`を書き込む。
+    /// `values`は`take`の結果のshapeへbroadcastできなければならない。
+    /// 同じ位置が複数回指定された場合は、最後の値が書き込まれる。
+    pub fn try_index_put<PI, I, PV>(
+        &mut self,
+        indices: &TensorBase<PI, I>,
+        values: &TensorBase<PV, E>,
+        axis: usize,
+    ) -> Result<(), TensorError>
+    where
+        PI: TensorPointer<Elem = I> + Cpu,
+        I: PrimInt,
+        PV: TensorPointer<Elem = E> + Cpu,
+    {
+        let (offsets, shape) = take_offsets(self, indices, axis)?;
+        let values = values.broadcast_iter(&shape)?;
+        let ptr = self.ptr.to_slice_mut().as_mut_ptr();
+        for (offset, x) in offsets.iter().zip(values) {
+            unsafe { *ptr.offset(*offset) = *x };
+        }
+        Ok(())
+    }
+
+    pub fn index_put<PI, I, PV>(
+        &mut self,
+        indices: &TensorBase<PI, I>,
+        values: &TensorBase<PV, E>,
+        axis: usize,
+    ) where
+        PI: TensorPointer<Elem = I> + Cpu,
+        I: PrimInt,
+        PV: TensorPointer<Elem = E> + Cpu,
+    {
+        self.try_index_put(indices, values, axis)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// `mask`がtrueの位置を`value`で埋める。`mask`はこのtensorのshapeへbroadcastできなければならない。
+    pub fn try_masked_fill<PM>(
+        &mut self,
+        mask: &TensorBase<PM, bool>,
+        value: E,
+    ) -> Result<(), TensorError>
+    where
+        PM: TensorPointer<Elem = bool> + Cpu,
+    {
+        let shape = self.shape();
+        for (x, m) in self.iter_mut().zip(mask.broadcast_iter(&shape)?) {
+            if *m {
+                *x = value;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn masked_fill<PM>(&mut self, mask: &TensorBase<PM, bool>, value: E)
+    where
+        PM: TensorPointer<Elem = bool> + Cpu,
+    {
+        self.try_masked_fill(mask, value)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+#[test]
+fn take_test() {
+    let a = CpuTensor::from_vec((0..12).collect::<Vec<i32>>(), Shape::new(vec![3, 4]));
+    let rows = CpuTensor::from_vec(vec![2i64, 0, -1], Shape::new(vec![3]));
+    let t = a.take(&rows, 0);
+    assert_eq!(t.shape(), Shape::new(vec![3, 4]));
+    assert_eq!(t.to_vec(), vec![8, 9, 10, 11, 0, 1, 2, 3, 8, 9, 10, 11]);
+    let cols = CpuTensor::from_vec(vec![1usize, 3, 0, 0], Shape::new(vec![2, 2]));
+    let t = a.take(&cols, 1);
+    assert_eq!(t.shape(), Shape::new(vec![3, 2, 2]));
+    assert_eq!(t.to_vec(), vec![1, 3, 0, 0, 5, 7, 4, 4, 9, 11, 8, 8]);
+}
+
+#[test]
+fn take_transposed_test() {
+    let a = CpuTensor::from_vec((0..6).collect::<Vec<i32>>(), Shape::new(vec![2, 3]));
+    let idx = CpuTensor::from_vec(vec![2i32, 0], Shape::new(vec![2]));
+    let t = a.t().index_select(0, &idx);
+    assert_eq!(t.to_vec(), vec![2, 5, 0, 3]);
+}
+
+#[test]
+fn take_out_of_bounds() {
+    let a = CpuTensor::from_vec((0..6).collect::<Vec<i32>>(), Shape::new(vec![2, 3]));
+    let idx = CpuTensor::from_vec(vec![0i32, 3], Shape::new(vec![2]));
+    assert_eq!(
+        a.try_take(&idx, 1).err(),
+        Some(TensorError::IndexOutOfBounds {
+            axis: 1,
+            index: 3,
+            dim: 3
+        })
+    );
+}
+
+#[test]
+fn gather_test() {
+    let a = CpuTensor::from_vec(vec![1, 2, 3, 4], Shape::new(vec![2, 2]));
+    let index = CpuTensor::from_vec(vec![0i64, 0, 1, 0], Shape::new(vec![2, 2]));
+    assert_eq!(a.gather(1, &index).to_vec(), vec![1, 1, 4, 3]);
+    assert_eq!(a.gather(0, &index).to_vec(), vec![1, 2, 3, 2]);
+}
+
+#[test]
+fn masked_select_test() {
+    let a = CpuTensor::from_vec((0..6).collect::<Vec<i32>>(), Shape::new(vec![2, 3]));
+    let mask = CpuTensor::from_vec(vec![true, false, true], Shape::new(vec![3]));
+    let s = a.masked_select(&mask);
+    assert_eq!(s.shape(), Shape::new(vec![4]));
+    assert_eq!(s.to_vec(), vec![0, 2, 3, 5]);
+}
+
+#[test]
+fn index_put_test() {
+    let mut a = CpuTensor::from_vec(vec![0; 6], Shape::new(vec![3, 2]));
+    let idx = CpuTensor::from_vec(vec![0u32, 2], Shape::new(vec![2]));
+    let values = CpuTensor::from_vec(vec![1, 2], Shape::new(vec![2]));
+    a.index_put(&idx, &values, 0);
+    assert_eq!(a.to_vec(), vec![1, 2, 0, 0, 1, 2]);
+}
+
+#[test]
+fn masked_fill_test() {
+    let mut a = CpuTensor::from_vec((0..6).collect::<Vec<i32>>(), Shape::new(vec![2, 3]));
+    let mask = CpuTensor::from_vec(vec![false, true], Shape::new(vec![2, 1]));
+    a.masked_fill(&mask, -1);
+    assert_eq!(a.to_vec(), vec![0, 1, 2, -1, -1, -1]);
+    let bad = CpuTensor::from_vec(vec![true, false], Shape::new(vec![2]));
+    assert!(a.try_masked_fill(&bad, 0).is_err());
+}
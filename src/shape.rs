@@ -2,7 +2,10 @@ use std::fmt::Debug;
 use std::iter::Iterator;
 use std::ops::{Deref, DerefMut};
 
-use crate::index::TensorIndex;
+use thiserror::Error;
+
+use crate::error::TensorError;
+use crate::index::{Inner, ResolvedInner, TensorIndex};
 // use for tests
 #[allow(unused_imports)]
 use crate::index;
@@ -48,6 +51,16 @@ impl Shape {
     pub fn add_axis_unchecked(&mut self, axis: usize) {
         self.insert(axis, 1);
     }
+
+    /// 軸が1つ以上あり、どの軸の大きさも負でないことを確認する。
+    pub fn validate(&self) -> Result<(), TensorError> {
+        if self.0.is_empty() || self.0.iter().any(|x| *x < 0) {
+            return Err(TensorError::InvalidShape {
+                shape: self.clone(),
+            });
+        }
+        Ok(())
+    }
 }
 
 impl Deref for Shape {
@@ -130,34 +143,103 @@ impl DerefMut for Stride {
     }
 }
 
-/// index is not collect then panic
-pub fn valid_index(shape: &Shape, stride: &Stride, index: &TensorIndex) {
-    if shape
+/// shapeとstrideの軸の数が等しいことを確認する
+fn check_num_dim(shape: &Shape, stride: &Stride) -> Result<(), TensorError> {
+    if shape.len() != stride.len() {
+        return Err(TensorError::NumDimMismatch {
+            expected: shape.len(),
+            got: stride.len(),
+        });
+    }
+    Ok(())
+}
+
+/// `Ellipsis`を展開し、各indexをshapeに合わせて解決する。
+/// 元のtensorの軸の番号も一緒に返す。`NewAxis`は対応する軸がないので`None`になる。
+fn resolve_index(
+    shape: &Shape,
+    index: &TensorIndex,
+) -> Result<Vec<(Option<usize>, ResolvedInner)>, TensorError> {
+    let mut axis = 0;
+    index
+        .expand_ellipsis(shape.num_dim())?
         .iter()
-        .zip(stride.iter())
-        .zip(index.iter())
-        .any(|((sh, st), idx)| {
-            let e = match idx.end {
-                Some(e) => e,
-                None => idx.start,
-            };
-            sh.abs() <= idx.start.abs() && sh.abs() <= e.abs() && st <= &idx.step.abs()
+        .map(|idx| {
+            if *idx == Inner::NewAxis {
+                return Ok((None, idx.resolve(axis, 1)?));
+            }
+            let resolved = idx.resolve(axis, shape[axis])?;
+            axis += 1;
+            Ok((Some(axis - 1), resolved))
         })
-    {
-        panic!("index is not collect");
+        .collect()
+}
+
+/// indexがshapeの範囲内にあることを確認する。
+///
+/// strideは0(broadcastされた軸)でも負でもよいので、indexの範囲はshapeのみで判定する。
+/// 負のindexは末尾から数え、rangeの範囲外の部分はNumPyと同様に切り詰めるため、エラーにならない。
+pub fn try_valid_index(
+    shape: &Shape,
+    _stride: &Stride,
+    index: &TensorIndex,
+) -> Result<(), TensorError> {
+    resolve_index(shape, index).map(|_| ())
+}
+
+/// index is not collect then panic
+pub fn valid_index(shape: &Shape, stride: &Stride, index: &TensorIndex) {
+    try_valid_index(shape, stride, index).unwrap_or_else(|e| panic!("{}", e));
+}
+
+pub fn try_cal_offset(
+    shape: &Shape,
+    stride: &Stride,
+    index: &TensorIndex,
+) -> Result<isize, TensorError> {
+    if !index.is_point_single_elm() {
+        return Err(TensorError::NotSingleElement);
     }
+    try_slice_update_offset(shape, stride, index)
 }
 
 pub fn cal_offset(shape: &Shape, stride: &Stride, index: &TensorIndex) -> isize {
-    valid_index(shape, stride, index);
-    if index.is_point_single_elm() {
-        stride
-            .iter()
-            .zip(index.iter())
-            .fold(0, |offset, (st, idx)| offset + st * idx.start)
-    } else {
-        panic!("this index points region so cannot cal_offset");
+    try_cal_offset(shape, stride, index).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_slice_update_shape_stride(
+    shape: &Shape,
+    stride: &Stride,
+    index: &TensorIndex,
+) -> Result<(Shape, Stride), TensorError> {
+    check_num_dim(shape, stride)?;
+    let resolved = resolve_index(shape, index)?;
+
+    // Pointの軸は取り除き、rangeの軸は要素数とstep倍したstrideを残す
+    let (shape_vec, mut stride_vec): (Vec<isize>, Vec<isize>) = resolved
+        .iter()
+        .filter_map(|(axis, idx)| {
+            let st = axis.map_or(0, |axis| stride[axis] * idx.step);
+            idx.len.map(|len| (len, st))
+        })
+        .unzip();
+
+    // NewAxisの軸のstrideは、後ろの軸が連続していればdefaultのstrideと一致するように決める
+    let new_axes = resolved
+        .iter()
+        .filter(|(_, idx)| idx.len.is_some())
+        .map(|(axis, _)| axis.is_none())
+        .collect::<Vec<bool>>();
+    for i in (0..stride_vec.len()).rev() {
+        if new_axes[i] {
+            stride_vec[i] = if i + 1 < stride_vec.len() {
+                stride_vec[i + 1] * shape_vec[i + 1]
+            } else {
+                1
+            };
+        }
     }
+    Ok((Shape::new(shape_vec), Stride::new(stride_vec)))
 }
 
 pub fn slice_update_shape_stride(
@@ -165,55 +247,336 @@ pub fn slice_update_shape_stride(
     stride: &Stride,
     index: &TensorIndex,
 ) -> (Shape, Stride) {
-    valid_index(shape, stride, index);
-    if !(shape.len() == stride.len() && shape.len() == index.len()) {
-        panic!("length of shape, stride, index must be same");
-    }
-    let mut skip_idx = vec![];
-    for (i, idx) in index.iter().enumerate() {
-        if idx.is_range() {
-            skip_idx.push(i);
-        }
+    try_slice_update_shape_stride(shape, stride, index).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// sliceした先頭の要素のoffsetを返す。
+/// sliceの結果が要素を持たない場合は、メモリにアクセスしないので0を返す。
+pub fn try_slice_update_offset(
+    shape: &Shape,
+    stride: &Stride,
+    index: &TensorIndex,
+) -> Result<isize, TensorError> {
+    check_num_dim(shape, stride)?;
+    let resolved = resolve_index(shape, index)?;
+    if resolved.iter().any(|(_, idx)| idx.len == Some(0)) {
+        return Ok(0);
     }
 
-    let mut shape_vec = index
+    Ok(resolved
         .iter()
-        .enumerate()
-        .map(|(idx, x)| {
-            let mut end = x.end.unwrap_or(x.start);
-            if end < 0 {
-                end += shape[idx];
+        .filter_map(|(axis, idx)| axis.map(|axis| stride[axis] * idx.start))
+        .sum())
+}
+
+pub fn slice_update_offset(shape: &Shape, stride: &Stride, index: &TensorIndex) -> isize {
+    try_slice_update_offset(shape, stride, index).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// 確保されたメモリの先頭から数えた`base + offset`が、長さ`len`のメモリに収まっていれば返す。
+/// 要素を確保していない場合は、先頭を指す0だけを受け付ける。
+pub(crate) fn checked_offset(base: usize, offset: isize, len: usize) -> Result<usize, TensorError> {
+    let abs = base as isize + offset;
+    if abs < 0 || (abs as usize >= len && !(abs == 0 && len == 0)) {
+        return Err(TensorError::OffsetOutOfBounds {
+            offset: abs.max(0) as usize,
+            len,
+        });
+    }
+    Ok(abs as usize)
+}
+
+/// axesの順番に軸を並べ替えたshape, strideを返す。
+/// axesが0..shape.len()の並べ替えになっていない場合はエラーを返す。
+pub fn try_permute_update_shape_stride(
+    shape: &Shape,
+    stride: &Stride,
+    axes: &[usize],
+) -> Result<(Shape, Stride), TensorError> {
+    let num_dim = shape.num_dim();
+    if axes.len() != num_dim {
+        return Err(TensorError::NumDimMismatch {
+            expected: num_dim,
+            got: axes.len(),
+        });
+    }
+    let mut seen = vec![false; num_dim];
+    for axis in axes.iter() {
+        match seen.get_mut(*axis) {
+            None => {
+                return Err(TensorError::InvalidAxis {
+                    axis: *axis,
+                    num_dim,
+                })
             }
-            let num_elm = (end - x.start).abs() + 1;
-            num_elm / x.step + num_elm % x.step
+            Some(true) => return Err(TensorError::DuplicateAxis { axis: *axis }),
+            Some(x) => *x = true,
+        }
+    }
+    let shape = Shape::new(axes.iter().map(|x| shape[*x]).collect());
+    let stride = Stride::new(axes.iter().map(|x| stride[*x]).collect());
+    Ok((shape, stride))
+}
+
+/// axesの順番に軸を並べ替えたshape, strideを返す。
+/// axesが0..shape.len()の並べ替えになっていない場合はpanicする。
+pub fn permute_update_shape_stride(
+    shape: &Shape,
+    stride: &Stride,
+    axes: &[usize],
+) -> (Shape, Stride) {
+    try_permute_update_shape_stride(shape, stride, axes).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// 最後の2つの軸を入れ替える並べ替えを返す。1次元以下の場合は並べ替えない。
+pub(crate) fn transpose_axes(num_dim: usize) -> Vec<usize> {
+    let mut axes = (0..num_dim).collect::<Vec<usize>>();
+    if num_dim >= 2 {
+        axes.swap(num_dim - 2, num_dim - 1);
+    }
+    axes
+}
+
+/// axisの位置に大きさ1の軸を挿入したshape, strideを返す。axisは0からnum_dimまで指定できる。
+/// 挿入する軸のstrideは、後ろの軸が連続していればdefaultのstrideと一致するように決める。
+pub fn unsqueeze_update_shape_stride(
+    shape: &Shape,
+    stride: &Stride,
+    axis: usize,
+) -> Result<(Shape, Stride), TensorError> {
+    if axis > shape.num_dim() {
+        return Err(TensorError::InvalidAxis {
+            axis,
+            num_dim: shape.num_dim() + 1,
+        });
+    }
+    let st = if axis < shape.num_dim() {
+        shape[axis] * stride[axis]
+    } else {
+        1
+    };
+    let mut shape = shape.clone();
+    let mut stride = stride.clone();
+    shape.add_axis_unchecked(axis);
+    stride.add_axis_unchecked(axis);
+    stride[axis] = st;
+    Ok((shape, stride))
+}
+
+/// 大きさ1の軸axisを取り除いたshape, strideを返す。
+pub fn squeeze_update_shape_stride(
+    shape: &Shape,
+    stride: &Stride,
+    axis: usize,
+) -> Result<(Shape, Stride), TensorError> {
+    if axis >= shape.num_dim() {
+        return Err(TensorError::InvalidAxis {
+            axis,
+            num_dim: shape.num_dim(),
+        });
+    }
+    if shape[axis] != 1 {
+        return Err(TensorError::SqueezeNonUnitAxis {
+            axis,
+            dim: shape[axis],
+        });
+    }
+    let mut shape = shape.clone();
+    let mut stride = stride.clone();
+    shape.remove(axis);
+    stride.remove(axis);
+    shape.validate()?;
+    Ok((shape, stride))
+}
+
+/// `start`から`end`まで(`end`を含む)の軸を1つにまとめたshape, strideを返す。
+/// 今のstrideで表せない場合はエラーを返す。
+pub fn flatten_update_shape_stride(
+    shape: &Shape,
+    stride: &Stride,
+    start: usize,
+    end: usize,
+) -> Result<(Shape, Stride), TensorError> {
+    let num_dim = shape.num_dim();
+    for axis in [start, end] {
+        if axis >= num_dim {
+            return Err(TensorError::InvalidAxis { axis, num_dim });
+        }
+    }
+    if start > end {
+        return Err(TensorError::InvalidAxisRange { start, end });
+    }
+    let mut to = shape[..start].to_vec();
+    to.push(shape[start..=end].iter().product());
+    to.extend_from_slice(&shape[end + 1..]);
+    let to = Shape::new(to);
+    match reshape_update_stride(shape, stride, &to) {
+        Some(stride) => Ok((to, stride)),
+        None => Err(TensorError::RequiresCopy {
+            shape: shape.clone(),
+            to,
+        }),
+    }
+}
+
+/// 軸axisを`sizes`の大きさの複数の軸に分けたshape, strideを返す。
+/// `sizes`の積はaxisの大きさと等しくなければならない。
+pub fn unflatten_update_shape_stride(
+    shape: &Shape,
+    stride: &Stride,
+    axis: usize,
+    sizes: &[isize],
+) -> Result<(Shape, Stride), TensorError> {
+    if axis >= shape.num_dim() {
+        return Err(TensorError::InvalidAxis {
+            axis,
+            num_dim: shape.num_dim(),
+        });
+    }
+    let sizes = Shape::new(sizes.to_vec());
+    sizes.validate()?;
+    if sizes.num_elms() != shape[axis] as usize {
+        return Err(TensorError::NumElmsMismatch {
+            expected: sizes.num_elms(),
+            got: shape[axis] as usize,
+            shape: sizes,
+        });
+    }
+    let mut to = shape[..axis].to_vec();
+    to.extend_from_slice(&sizes);
+    to.extend_from_slice(&shape[axis + 1..]);
+    let to = Shape::new(to);
+    match reshape_update_stride(shape, stride, &to) {
+        Some(stride) => Ok((to, stride)),
+        None => Err(TensorError::RequiresCopy {
+            shape: shape.clone(),
+            to,
+        }),
+    }
+}
+
+/// 大きさ1の軸を`to`の大きさに伸ばしたshape, strideを返す。`to`の-1は元の大きさのままにする。
+/// 伸ばした軸のstrideは0になる。
+pub fn expand_update_shape_stride(
+    shape: &Shape,
+    stride: &Stride,
+    to: &Shape,
+) -> Result<(Shape, Stride), TensorError> {
+    let pad = to.num_dim().checked_sub(shape.num_dim()).ok_or_else(|| {
+        TensorError::Broadcast(BroadcastError {
+            shapes: vec![shape.clone(), to.clone()],
         })
-        .collect::<Vec<isize>>();
+    })?;
+    let to = Shape::new(
+        to.iter()
+            .enumerate()
+            .map(|(i, dim)| match (*dim, i.checked_sub(pad)) {
+                (-1, Some(j)) => shape[j],
+                _ => *dim,
+            })
+            .collect(),
+    );
+    to.validate()?;
+    let stride = broadcast_update_stride(shape, stride, &to)?;
+    Ok((to, stride))
+}
 
-    let mut stride_vec = index
+/// shape, strideで表されるtensorを、データをコピーせずに`to`のshapeで表した時のstrideを返す。
+/// 既存のstrideで表せない場合は`None`を返す。shapeと`to`の要素数は等しくなければならない。
+///
+/// 大きさ1の軸を除いた上で、要素数が等しくなるように元の軸と新しい軸をまとめていき、
+/// まとめた元の軸の中でメモリが連続している場合のみviewにできる。(NumPyと同じ方法)
+pub fn reshape_update_stride(shape: &Shape, stride: &Stride, to: &Shape) -> Option<Stride> {
+    if to.num_elms() == 0 {
+        return Some(to.default_stride());
+    }
+    let (old_shape, old_stride): (Vec<isize>, Vec<isize>) = shape
         .iter()
         .zip(stride.iter())
-        .map(|(idx, st)| st * idx.step)
-        .collect::<Vec<isize>>();
+        .filter(|(sh, _)| **sh != 1)
+        .map(|(sh, st)| (*sh, *st))
+        .unzip();
 
-    for x in skip_idx.iter() {
-        let _ = shape_vec.remove(*x);
-        let _ = stride_vec.remove(*x);
+    let mut res = vec![0; to.num_dim()];
+    let (mut oi, mut oj, mut ni, mut nj) = (0, 1, 0, 1);
+    while ni < to.num_dim() && oi < old_shape.len() {
+        let mut np = to[ni];
+        let mut op = old_shape[oi];
+        while np != op {
+            if np < op {
+                np *= to[nj];
+                nj += 1;
+            } else {
+                op *= old_shape[oj];
+                oj += 1;
+            }
+        }
+        if (oi..oj - 1).any(|ok| old_stride[ok] != old_shape[ok + 1] * old_stride[ok + 1]) {
+            return None;
+        }
+        res[nj - 1] = old_stride[oj - 1];
+        for nk in (ni + 1..nj).rev() {
+            res[nk - 1] = res[nk] * to[nk];
+        }
+        ni = nj;
+        nj += 1;
+        oi = oj;
+        oj += 1;
+    }
+    // 末尾に残った大きさ1の軸
+    let last = if ni >= 1 { res[ni - 1] } else { 1 };
+    for r in res[ni..].iter_mut() {
+        *r = last;
     }
-    let shape = Shape::new(shape_vec);
-    let stride = Stride::new(stride_vec);
-    (shape, stride)
+    Some(Stride::new(res))
 }
 
-pub fn slice_update_offset(shape: &Shape, stride: &Stride, index: &TensorIndex) -> isize {
-    valid_index(shape, stride, index);
-    if !(shape.len() == stride.len() && shape.len() == index.len()) {
-        panic!("length of shape, stride, index must be same");
+#[derive(Error, Clone, PartialEq, Eq, Debug)]
+#[error("shapes {shapes:?} cannot be broadcast together")]
+pub struct BroadcastError {
+    pub shapes: Vec<Shape>,
+}
+
+/// NumPyと同じ規則で全てのshapeをbroadcastした結果のshapeを返す。
+/// 次元数が少ないshapeは先頭に1を補って比較し、各軸の大きさは等しいか、どちらかが1でなければならない。
+pub fn broadcast_shapes(shapes: &[Shape]) -> Result<Shape, BroadcastError> {
+    let num_dim = shapes.iter().map(|s| s.num_dim()).max().unwrap_or(0);
+    let mut res = vec![1; num_dim];
+    for shape in shapes.iter() {
+        let pad = num_dim - shape.num_dim();
+        for (r, dim) in res[pad..].iter_mut().zip(shape.iter()) {
+            if *r == 1 {
+                *r = *dim;
+            } else if *dim != 1 && *dim != *r {
+                return Err(BroadcastError {
+                    shapes: shapes.to_vec(),
+                });
+            }
+        }
     }
+    Ok(Shape::new(res))
+}
 
-    stride
-        .iter()
-        .zip(index.iter())
-        .fold(0, |prev, (st, idx)| prev + st * idx.start)
+/// shape, strideで表されるtensorを`to`にbroadcastした時のstrideを返す。
+/// broadcastで伸ばされる軸のstrideは0になるため、メモリのコピーは発生しない。
+pub fn broadcast_update_stride(
+    shape: &Shape,
+    stride: &Stride,
+    to: &Shape,
+) -> Result<Stride, BroadcastError> {
+    let err = || BroadcastError {
+        shapes: vec![shape.clone(), to.clone()],
+    };
+    let pad = to.num_dim().checked_sub(shape.num_dim()).ok_or_else(err)?;
+    let mut res = vec![0; to.num_dim()];
+    for (i, (sh, st)) in shape.iter().zip(stride.iter()).enumerate() {
+        if *sh == to[i + pad] {
+            res[i + pad] = *st;
+        } else if *sh != 1 {
+            return Err(err());
+        }
+    }
+    Ok(Stride::new(res))
 }
 
 macro_rules! impl_defalut_stride_test {
@@ -430,6 +793,24 @@ impl_slice_update_test!(
     vec![2]
 );
 
+impl_slice_update_test!(
+    stride_shape_update_zero_stride,
+    vec![4, 3],
+    vec![0, 1],
+    index![1..3, ..],
+    vec![2, 3],
+    vec![0, 1]
+);
+
+impl_slice_update_test!(
+    stride_shape_update_default_stride_1d_step_3,
+    vec![20],
+    vec![1],
+    index![0..8;3],
+    vec![3],
+    vec![3]
+);
+
 macro_rules! impl_slice_update_test {
     ($fn_name:ident, $shape:expr, $stride:expr, $index:expr, $ans:expr) => {
         #[test]
@@ -509,3 +890,204 @@ impl_add_axis!(
     3,
     Stride::new(vec![3, 4, 5, 1])
 );
+
+macro_rules! impl_broadcast_shapes_test {
+    (@ok $fn_name:ident, [$($shape:expr),*], $ans:expr) => {
+        #[test]
+        fn $fn_name() {
+            let shapes = vec![$(Shape::new($shape)),*];
+            assert_eq!(broadcast_shapes(&shapes), Ok(Shape::new($ans)));
+        }
+    };
+
+    (@err $fn_name:ident, [$($shape:expr),*]) => {
+        #[test]
+        fn $fn_name() {
+            let shapes = vec![$(Shape::new($shape)),*];
+            assert!(broadcast_shapes(&shapes).is_err());
+        }
+    };
+}
+
+impl_broadcast_shapes_test!(@ok broadcast_shapes_same, [vec![2, 3], vec![2, 3]], vec![2, 3]);
+impl_broadcast_shapes_test!(@ok broadcast_shapes_pad, [vec![4, 2, 3], vec![3]], vec![4, 2, 3]);
+impl_broadcast_shapes_test!(@ok broadcast_shapes_one, [vec![3, 1], vec![1, 4]], vec![3, 4]);
+impl_broadcast_shapes_test!(
+    @ok broadcast_shapes_three,
+    [vec![5, 1, 1], vec![1, 4, 1], vec![3]],
+    vec![5, 4, 3]
+);
+impl_broadcast_shapes_test!(@err broadcast_shapes_mismatch, [vec![2, 3], vec![2]]);
+impl_broadcast_shapes_test!(@err broadcast_shapes_mismatch_3, [vec![2, 1], vec![8, 4, 3]]);
+
+macro_rules! impl_broadcast_update_stride_test {
+    (@ok $fn_name:ident, $shape:expr, $stride:expr, $to:expr, $ans:expr) => {
+        #[test]
+        fn $fn_name() {
+            let shape = Shape::new($shape);
+            let stride = Stride::new($stride);
+            let res = broadcast_update_stride(&shape, &stride, &Shape::new($to));
+            assert_eq!(res, Ok(Stride::new($ans)));
+        }
+    };
+
+    (@err $fn_name:ident, $shape:expr, $stride:expr, $to:expr) => {
+        #[test]
+        fn $fn_name() {
+            let shape = Shape::new($shape);
+            let stride = Stride::new($stride);
+            let res = broadcast_update_stride(&shape, &stride, &Shape::new($to));
+            assert!(res.is_err());
+        }
+    };
+}
+
+impl_broadcast_update_stride_test!(@ok broadcast_stride_pad, vec![3], vec![1], vec![2, 3], vec![0, 1]);
+impl_broadcast_update_stride_test!(
+    @ok broadcast_stride_one,
+    vec![3, 1],
+    vec![1, 1],
+    vec![3, 4],
+    vec![1, 0]
+);
+impl_broadcast_update_stride_test!(@err broadcast_stride_shrink, vec![2, 3], vec![3, 1], vec![3]);
+impl_broadcast_update_stride_test!(@err broadcast_stride_mismatch, vec![2], vec![1], vec![3]);
+
+macro_rules! impl_permute_test {
+    ($fn_name:ident, $shape:expr, $stride:expr, $axes:expr, $ans_shape:expr, $ans_stride:expr) => {
+        #[test]
+        fn $fn_name() {
+            let shape = Shape::new($shape);
+            let stride = Stride::new($stride);
+            let (shape, stride) = permute_update_shape_stride(&shape, &stride, &$axes);
+            assert_eq!(Shape::new($ans_shape), shape);
+            assert_eq!(Stride::new($ans_stride), stride);
+        }
+    };
+}
+
+impl_permute_test!(
+    permute_2d,
+    vec![2, 3],
+    vec![3, 1],
+    [1, 0],
+    vec![3, 2],
+    vec![1, 3]
+);
+impl_permute_test!(
+    permute_3d,
+    vec![2, 3, 4],
+    vec![12, 4, 1],
+    [2, 0, 1],
+    vec![4, 2, 3],
+    vec![1, 12, 4]
+);
+
+#[test]
+#[should_panic]
+fn permute_duplicate_axes() {
+    let shape = Shape::new(vec![2, 3]);
+    let stride = shape.default_stride();
+    permute_update_shape_stride(&shape, &stride, &[0, 0]);
+}
+
+macro_rules! impl_reshape_stride_test {
+    ($fn_name:ident, $shape:expr, $stride:expr, $to:expr, $ans:expr) => {
+        #[test]
+        fn $fn_name() {
+            let shape = Shape::new($shape);
+            let stride = Stride::new($stride);
+            let res = reshape_update_stride(&shape, &stride, &Shape::new($to));
+            assert_eq!(res, $ans.map(Stride::new));
+        }
+    };
+}
+
+impl_reshape_stride_test!(
+    reshape_stride_default,
+    vec![2, 3, 4],
+    vec![12, 4, 1],
+    vec![6, 4],
+    Some(vec![4, 1])
+);
+impl_reshape_stride_test!(
+    reshape_stride_split_axis,
+    vec![6, 4],
+    vec![4, 1],
+    vec![2, 3, 2, 2],
+    Some(vec![12, 4, 2, 1])
+);
+impl_reshape_stride_test!(
+    reshape_stride_sliced_rows,
+    vec![2, 3, 4],
+    vec![24, 4, 1],
+    vec![2, 12],
+    Some(vec![24, 1])
+);
+impl_reshape_stride_test!(
+    reshape_stride_transposed,
+    vec![3, 2],
+    vec![1, 3],
+    vec![6],
+    None::<Vec<isize>>
+);
+impl_reshape_stride_test!(
+    reshape_stride_transposed_split,
+    vec![4, 3],
+    vec![1, 4],
+    vec![2, 2, 3],
+    Some(vec![2, 1, 4])
+);
+impl_reshape_stride_test!(
+    reshape_stride_ones,
+    vec![1, 4, 1],
+    vec![4, 1, 1],
+    vec![2, 1, 2, 1],
+    Some(vec![2, 2, 1, 1])
+);
+
+#[test]
+fn unsqueeze_squeeze_test() {
+    let shape = Shape::new(vec![2, 3]);
+    let stride = shape.default_stride();
+    let (s0, st0) = unsqueeze_update_shape_stride(&shape, &stride, 1).unwrap();
+    assert_eq!(s0, Shape::new(vec![2, 1, 3]));
+    assert!(s0.is_default_stride(&st0));
+    let (s1, st1) = unsqueeze_update_shape_stride(&shape, &stride, 2).unwrap();
+    assert_eq!(s1, Shape::new(vec![2, 3, 1]));
+    assert!(s1.is_default_stride(&st1));
+    assert!(unsqueeze_update_shape_stride(&shape, &stride, 3).is_err());
+    let (s, st) = squeeze_update_shape_stride(&s0, &st0, 1).unwrap();
+    assert_eq!((s, st), (shape.clone(), stride.clone()));
+    assert_eq!(
+        squeeze_update_shape_stride(&shape, &stride, 0),
+        Err(TensorError::SqueezeNonUnitAxis { axis: 0, dim: 2 })
+    );
+}
+
+#[test]
+fn flatten_unflatten_test() {
+    let shape = Shape::new(vec![2, 3, 4]);
+    let stride = shape.default_stride();
+    let (s, st) = flatten_update_shape_stride(&shape, &stride, 1, 2).unwrap();
+    assert_eq!(s, Shape::new(vec![2, 12]));
+    assert_eq!(st, Stride::new(vec![12, 1]));
+    let (s, st) = unflatten_update_shape_stride(&s, &st, 1, &[3, 4]).unwrap();
+    assert_eq!((s, st), (shape.clone(), stride.clone()));
+    let (s, st) = permute_update_shape_stride(&shape, &stride, &[0, 2, 1]);
+    assert!(matches!(
+        flatten_update_shape_stride(&s, &st, 1, 2),
+        Err(TensorError::RequiresCopy { .. })
+    ));
+    assert!(unflatten_update_shape_stride(&shape, &stride, 0, &[3]).is_err());
+}
+
+#[test]
+fn expand_test() {
+    let shape = Shape::new(vec![3, 1]);
+    let stride = shape.default_stride();
+    let (s, st) = expand_update_shape_stride(&shape, &stride, &Shape::new(vec![2, -1, 4])).unwrap();
+    assert_eq!(s, Shape::new(vec![2, 3, 4]));
+    assert_eq!(st, Stride::new(vec![0, 1, 0]));
+    assert!(expand_update_shape_stride(&shape, &stride, &Shape::new(vec![2, 4])).is_err());
+}
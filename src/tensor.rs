@@ -1,4 +1,4 @@
-use crate::pointer_cpu::{OwnedCpu, ViewCpu, ViewMutCpu};
+use crate::pointer_cpu::{ArcCpu, MmapCpu, OwnedCpu, ViewCpu, ViewMutCpu};
 use crate::pointer_traits::TensorPointer;
 use crate::shape::{Shape, Stride};
 
@@ -13,8 +13,33 @@ where
 }
 
 pub type CpuTensor<E> = TensorBase<OwnedCpu<E>, E>;
-pub type CpuViewTensor<E> = TensorBase<ViewCpu<E>, E>;
-pub type CpuViewMutTensor<E> = TensorBase<ViewMutCpu<E>, E>;
+/// メモリを共有するtensor。cloneはO(1)で、書き込む前に共有されていればコピーする。
+pub type CpuArcTensor<E> = TensorBase<ArcCpu<E>, E>;
+pub type CpuViewTensor<'a, E> = TensorBase<ViewCpu<'a, E>, E>;
+pub type CpuViewMutTensor<'a, E> = TensorBase<ViewMutCpu<'a, E>, E>;
+/// メモリマップしたファイルを指すtensor。`CpuMmapTensor::open`で作る。
+pub type CpuMmapTensor<E> = TensorBase<MmapCpu<E>, E>;
+
+impl<P, E> Clone for TensorBase<P, E>
+where
+    P: TensorPointer<Elem = E> + Clone,
+{
+    fn clone(&self) -> Self {
+        TensorBase {
+            ptr: self.ptr.clone(),
+            shape: self.shape.clone(),
+            stride: self.stride.clone(),
+            num_elm: self.num_elm,
+        }
+    }
+}
+
+/// コピーが必要な場合のみ新しくメモリを確保した結果
+/// 元のtensorのメモリをそのまま参照できる場合は`View`、コピーした場合は`Owned`になる。
+pub enum CpuCowTensor<'a, E: Copy> {
+    View(CpuViewTensor<'a, E>),
+    Owned(CpuTensor<E>),
+}
 
 #[test]
 fn to_view_to_onwend_test() {
@@ -68,3 +93,100 @@ fn index_test() {
     ];
     assert_eq!(a_vec, ans);
 }
+
+#[test]
+fn broadcast_to_test() {
+    let a = CpuTensor::from_vec(vec![0, 1, 2], Shape::new(vec![3]));
+    let b = a.broadcast_to(Shape::new(vec![2, 3])).unwrap();
+    assert_eq!(b.stride(), Stride::new(vec![0, 1]));
+    assert_eq!(b.into_owned().to_vec(), vec![0, 1, 2, 0, 1, 2]);
+}
+
+#[test]
+fn broadcast_to_view_test() {
+    use crate::index;
+    let a = CpuTensor::from_vec((0..6).collect(), Shape::new(vec![2, 3]));
+    let col = a.slice(index![.., 1]);
+    let mut col = col.into_owned();
+    col.add_axis(1);
+    let b = col.to_view().broadcast_to(Shape::new(vec![2, 2])).unwrap();
+    assert_eq!(b.into_owned().to_vec(), vec![1, 1, 4, 4]);
+}
+
+#[test]
+fn broadcast_to_error_test() {
+    let a = CpuTensor::from_vec(vec![0, 1, 2], Shape::new(vec![3]));
+    assert!(a.broadcast_to(Shape::new(vec![3, 2])).is_err());
+}
+
+#[test]
+fn negative_index_test() {
+    use crate::index;
+    let a = CpuTensor::from_vec((0..12).collect(), Shape::new(vec![3, 4]));
+    let last_row = a.slice(index![-1, ..]);
+    assert_eq!(last_row.into_owned().to_vec(), vec![8, 9, 10, 11]);
+    let last_two = a.slice(index![.., -2..]);
+    assert_eq!(last_two.shape(), Shape::new(vec![3, 2]));
+    assert_eq!(last_two.into_owned().to_vec(), vec![2, 3, 6, 7, 10, 11]);
+    let head = a.slice(index![..-2, 0]);
+    assert_eq!(head.into_owned().to_vec(), vec![0]);
+}
+
+#[test]
+fn negative_step_test() {
+    use crate::index;
+    let a = CpuTensor::from_vec((0..12).collect(), Shape::new(vec![3, 4]));
+    let rev = a.slice(index![..;-1, ..]);
+    assert_eq!(rev.stride(), Stride::new(vec![-4, 1]));
+    assert_eq!(
+        rev.into_owned().to_vec(),
+        vec![8, 9, 10, 11, 4, 5, 6, 7, 0, 1, 2, 3]
+    );
+    let rev = a.slice(index![1, ..;-2]);
+    assert_eq!(rev.into_owned().to_vec(), vec![7, 5]);
+}
+
+#[test]
+fn index_out_of_range_error() {
+    use crate::error::TensorError;
+    use crate::index;
+    let a = CpuTensor::from_vec((0..12).collect::<Vec<i32>>(), Shape::new(vec![3, 4]));
+    assert_eq!(
+        a.try_slice(index![-4, ..]).err(),
+        Some(TensorError::IndexOutOfBounds {
+            axis: 0,
+            index: -4,
+            dim: 3
+        })
+    );
+    let empty = a.slice(index![5.., ..]);
+    assert_eq!(empty.shape(), Shape::new(vec![0, 4]));
+}
+
+#[test]
+fn ellipsis_index_test() {
+    use crate::index;
+    let a = CpuTensor::from_vec((0..24).collect(), Shape::new(vec![2, 3, 4]));
+    let last = a.slice(index![..., 0]);
+    assert_eq!(last.shape(), Shape::new(vec![2, 3]));
+    assert_eq!(last.into_owned().to_vec(), vec![0, 4, 8, 12, 16, 20]);
+    let first = a.slice(index![1, ...]);
+    assert_eq!(first.shape(), Shape::new(vec![3, 4]));
+    let b = CpuTensor::from_vec((0..4).collect(), Shape::new(vec![4]));
+    assert_eq!(b.slice(index![..., 1..3]).into_owned().to_vec(), vec![1, 2]);
+}
+
+#[test]
+fn new_axis_index_test() {
+    use crate::index;
+    let a = CpuTensor::from_vec((0..12).collect(), Shape::new(vec![3, 4]));
+    let b = a.slice(index![NewAxis, .., 1..3]);
+    assert_eq!(b.shape(), Shape::new(vec![1, 3, 2]));
+    assert_eq!(b.into_owned().to_vec(), vec![1, 2, 5, 6, 9, 10]);
+    let c = a.slice(index![.., NewAxis, ..]);
+    assert_eq!(c.shape(), Shape::new(vec![3, 1, 4]));
+    assert_eq!(c.stride(), Stride::new(vec![4, 4, 1]));
+    assert!(c.contiguous().is_view());
+    let d = a.slice(index![..., NewAxis]);
+    assert_eq!(d.shape(), Shape::new(vec![3, 4, 1]));
+}
@@ -1,25 +1,38 @@
 use std::ptr::NonNull;
 
-use crate::pointer_traits::TensorPointer;
-use crate::shape::{Shape, Stride};
+use crate::error::TensorError;
+use crate::pointer_traits::{Owned, TensorPointer};
+use crate::shape::{unsqueeze_update_shape_stride, Shape, Stride};
 use crate::tensor::TensorBase;
 
-impl<P: TensorPointer<Elem = E>, E: Copy> TensorBase<P, E> {
-    pub fn from_vec(v: Vec<E>, shape: Shape) -> Self {
+impl<P: Owned<Elem = E>, E: Copy> TensorBase<P, E> {
+    /// vの長さとshapeの要素数が一致しない場合はエラーを返す。
+    pub fn try_from_vec(v: Vec<E>, shape: Shape) -> Result<Self, TensorError> {
+        shape.validate()?;
         let num_elm = v.len();
-        let ptr = P::from_vec(v);
-        let stride = shape.default_stride();
         if num_elm != shape.num_elms() {
-            panic!("shape is not collect");
+            return Err(TensorError::NumElmsMismatch {
+                expected: shape.num_elms(),
+                got: num_elm,
+                shape,
+            });
         }
-        TensorBase {
+        let ptr = P::from_vec(v);
+        let stride = shape.default_stride();
+        Ok(TensorBase {
             ptr,
             shape,
             stride,
             num_elm,
-        }
+        })
+    }
+
+    pub fn from_vec(v: Vec<E>, shape: Shape) -> Self {
+        Self::try_from_vec(v, shape).unwrap_or_else(|e| panic!("{}", e))
     }
+}
 
+impl<P: TensorPointer<Elem = E>, E: Copy> TensorBase<P, E> {
     #[inline]
     pub fn to_vec(&self) -> Vec<E> {
         self.ptr.to_vec()
@@ -45,16 +58,6 @@ impl<P: TensorPointer<Elem = E>, E: Copy> TensorBase<P, E> {
         self.shape.clone().to_vec()
     }
 
-    /// Change SHAPE to the one entered. stride is changed to the default stride
-    #[inline]
-    pub fn reshape(&mut self, shape: Shape) {
-        if shape.num_elms() != self.shape().num_elms() {
-            panic!("The input shape and the number of elements in the sensor do not match.");
-        }
-        self.stride = shape.default_stride();
-        self.shape = shape;
-    }
-
     #[inline]
     pub fn is_column_major(&self) -> bool {
         let dim = self.shape();
@@ -78,21 +81,36 @@ impl<P: TensorPointer<Elem = E>, E: Copy> TensorBase<P, E> {
     }
 
     #[inline]
-    pub fn swap_axis(&mut self, a: usize, b: usize) {
-        if usize::max(a, b) >= self.shape.len() {
-            panic!("swap axis argument is smaller than axis length");
+    pub fn try_swap_axis(&mut self, a: usize, b: usize) -> Result<(), TensorError> {
+        let num_dim = self.shape.num_dim();
+        if usize::max(a, b) >= num_dim {
+            return Err(TensorError::InvalidAxis {
+                axis: usize::max(a, b),
+                num_dim,
+            });
         }
         self.shape.swap(a, b);
         self.stride.swap(a, b);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn swap_axis(&mut self, a: usize, b: usize) {
+        self.try_swap_axis(a, b).unwrap_or_else(|e| panic!("{}", e));
+    }
+
+    /// axisの位置に大きさ1の軸を追加する。axisは0からnum_dimまで指定できる。
+    #[inline]
+    pub fn try_add_axis(&mut self, axis: usize) -> Result<(), TensorError> {
+        let (shape, stride) = unsqueeze_update_shape_stride(&self.shape, &self.stride, axis)?;
+        self.shape = shape;
+        self.stride = stride;
+        Ok(())
     }
 
     #[inline]
     pub fn add_axis(&mut self, axis: usize) {
-        if axis < self.shape().num_dim() {
-            panic!("axis must be smaller than length of shape");
-        }
-        self.shape.add_axis_unchecked(axis);
-        self.stride.add_axis_unchecked(axis);
+        self.try_add_axis(axis).unwrap_or_else(|e| panic!("{}", e));
     }
 }
 
@@ -101,7 +119,7 @@ impl<P: TensorPointer<Elem = E>, E: Copy> TensorBase<P, E> {
 fn reshape_test() {
     use crate::tensor::CpuTensor;
     let a = vec![10, 20, 30];
-    let mut a = CpuTensor::from_vec(a, Shape::new(vec![3]));
+    let a = CpuTensor::from_vec(a, Shape::new(vec![3]));
     a.reshape(Shape::new(vec![100000]));
 }
 
@@ -138,3 +156,44 @@ fn swap_axis_test() {
     let a_v_v = a_v.into_owned().to_vec();
     assert_eq!(a_v_v, vec![0, 4, 2, 6, 1, 5, 3, 7]);
 }
+
+#[test]
+fn try_from_vec_error() {
+    use crate::tensor::CpuTensor;
+    let a = CpuTensor::try_from_vec(vec![1., 2., 3.], Shape::new(vec![2, 2]));
+    assert_eq!(
+        a.err(),
+        Some(TensorError::NumElmsMismatch {
+            shape: Shape::new(vec![2, 2]),
+            expected: 4,
+            got: 3,
+        })
+    );
+    let a = CpuTensor::try_from_vec(vec![1., 2.], Shape::new(vec![-1, -2]));
+    assert!(matches!(a, Err(TensorError::InvalidShape { .. })));
+}
+
+#[test]
+fn try_swap_axis_error() {
+    use crate::tensor::CpuTensor;
+    let mut a = CpuTensor::from_vec((0..6).collect::<Vec<i32>>(), Shape::new(vec![2, 3]));
+    assert_eq!(
+        a.try_swap_axis(0, 2),
+        Err(TensorError::InvalidAxis {
+            axis: 2,
+            num_dim: 2
+        })
+    );
+    assert_eq!(a.shape(), Shape::new(vec![2, 3]));
+}
+
+#[test]
+fn add_axis_test() {
+    use crate::tensor::CpuTensor;
+    let mut a = CpuTensor::from_vec((0..6).collect::<Vec<i32>>(), Shape::new(vec![2, 3]));
+    a.add_axis(0);
+    assert_eq!(a.shape(), Shape::new(vec![1, 2, 3]));
+    a.add_axis(3);
+    assert_eq!(a.shape(), Shape::new(vec![1, 2, 3, 1]));
+    assert!(a.try_add_axis(5).is_err());
+}
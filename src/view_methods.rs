@@ -1,56 +1,108 @@
-use std::convert::TryInto;
 use std::fmt::Debug;
 
 use num_traits::Num;
 
-use crate::pointer_cpu::{OwnedCpu, ViewCpu};
-use crate::pointer_traits::{Cpu, Mut, TensorPointer, View};
-use crate::shape::cal_offset;
-use crate::tensor::{CpuTensor, CpuViewMutTensor, TensorBase};
+use crate::error::TensorError;
+use crate::memory_pool::collect_vec;
+use crate::pointer_cpu::OwnedCpu;
+use crate::pointer_traits::{Cpu, Owned, TensorPointer, View};
+use crate::shape::{
+    broadcast_update_stride, expand_update_shape_stride, flatten_update_shape_stride,
+    reshape_update_stride, squeeze_update_shape_stride, transpose_axes,
+    try_permute_update_shape_stride, unflatten_update_shape_stride, unsqueeze_update_shape_stride,
+    BroadcastError, Shape, Stride,
+};
+use crate::tensor::{CpuCowTensor, CpuTensor, CpuViewMutTensor, CpuViewTensor, TensorBase};
 
 #[inline]
 fn cpu_shrink_to<P, E>(a: TensorBase<P, E>) -> OwnedCpu<E>
 where
-    // P: View<AccessOutput = ViewCpu<E>, OwnedOutput = OwnedCpu<E>> + TensorPointer<Elem = E>,
-    P: View<ViewCpu<E>, OwnedCpu<E>> + TensorPointer<Elem = E>,
-    E: Copy + Num + Debug,
+    P: TensorPointer<Elem = E> + Cpu,
+    E: Copy,
 {
-    let shape = a.shape.clone();
-    let default_stride = shape.default_stride();
-    let num_elm = shape.num_elms();
+    OwnedCpu::from_vec(a.iter().copied().collect())
+}
 
-    let mut v: Vec<E> = Vec::with_capacity(num_elm);
-    for _ in 0..num_elm {
-        v.push(E::zero());
+/// 同じメモリ、shape, strideを指すviewを作る
+#[inline]
+fn cpu_view<P, E>(a: &TensorBase<P, E>) -> TensorBase<P::Ref<'_>, E>
+where
+    P: View<OwnedCpu<E>> + TensorPointer<Elem = E>,
+    E: Copy,
+{
+    TensorBase {
+        ptr: a.ptr.access_by_offset_region(0, a.ptr.len()),
+        shape: a.shape.clone(),
+        stride: a.stride.clone(),
+        num_elm: a.num_elm,
     }
+}
 
-    let mut ptr = OwnedCpu::from_vec(v);
+/// strideがdefaultであればviewを、そうでなければコピーしたtensorを返す
+#[inline]
+pub(crate) fn cpu_contiguous<E: Copy>(a: CpuViewTensor<'_, E>) -> CpuCowTensor<'_, E> {
+    if a.shape.is_default_stride(&a.stride) {
+        CpuCowTensor::View(a)
+    } else {
+        let shape = a.shape.clone();
+        CpuCowTensor::Owned(TensorBase::from_vec(collect_vec(a.iter().copied()), shape))
+    }
+}
 
-    let shape_iter = shape.to_shape_iter();
-    for index in shape_iter {
-        let ptr_offset: usize = cal_offset(&shape, &default_stride, &index)
-            .try_into()
-            .unwrap();
-        let a_offset = cal_offset(&shape, &a.stride, &index).try_into().unwrap();
-        let other_ptr = a.ptr.access_by_offset_region(a_offset, 1);
-        ptr.assign_region(&other_ptr, ptr_offset, 1);
+/// strideで表せる場合はviewを、表せない場合はコピーしたtensorを返す
+#[inline]
+pub(crate) fn cpu_reshape<E: Copy>(
+    a: CpuViewTensor<'_, E>,
+    shape: Shape,
+) -> Result<CpuCowTensor<'_, E>, TensorError> {
+    shape.validate()?;
+    if shape.num_elms() != a.shape.num_elms() {
+        return Err(TensorError::NumElmsMismatch {
+            expected: shape.num_elms(),
+            got: a.shape.num_elms(),
+            shape,
+        });
+    }
+    match reshape_update_stride(&a.shape, &a.stride, &shape) {
+        Some(stride) => {
+            let mut view = a;
+            view.shape = shape;
+            view.stride = stride;
+            Ok(CpuCowTensor::View(view))
+        }
+        None => Ok(CpuCowTensor::Owned(TensorBase::try_from_vec(
+            a.iter().copied().collect(),
+            shape,
+        )?)),
     }
-    ptr
+}
+
+#[inline]
+fn cpu_broadcast_to<E: Copy>(
+    a: CpuViewTensor<'_, E>,
+    shape: Shape,
+) -> Result<CpuViewTensor<'_, E>, BroadcastError> {
+    let stride = broadcast_update_stride(&a.shape, &a.stride, &shape)?;
+    let mut view = a;
+    view.shape = shape;
+    view.stride = stride;
+    Ok(view)
 }
 
 impl<P: TensorPointer<Elem = E>, E> TensorBase<P, E>
 where
-    P: TensorPointer<Elem = E> + Cpu + View<ViewCpu<E>, OwnedCpu<E>>,
+    P: TensorPointer<Elem = E> + Cpu + View<OwnedCpu<E>>,
     E: Copy + Num + Debug,
 {
     #[inline]
     pub fn into_owned(self) -> CpuTensor<E> {
         if self.stride == self.shape.default_stride() {
+            let num_elm = self.shape.num_elms();
             TensorBase {
-                ptr: self.ptr.to_owned(),
+                ptr: OwnedCpu::from_vec(self.ptr.to_slice()[..num_elm].to_vec()),
                 shape: self.shape.clone(),
                 stride: self.stride.clone(),
-                num_elm: self.num_elm,
+                num_elm,
             }
         } else {
             let shape = self.shape.clone();
@@ -75,12 +127,226 @@ where
     }
 }
 
-impl<E: Copy> CpuViewMutTensor<E> {
+macro_rules! impl_view_transform {
+    ($name:ident, $lt:lifetime) => {
+        impl<'a, E: Copy> $name<'a, E> {
+            /// 同じメモリを参照するviewを返す。
+            #[inline]
+            pub fn to_view(&self) -> CpuViewTensor<$lt, E> {
+                cpu_view(self)
+            }
+
+            /// shapeへbroadcastしたviewを返す。
+            /// broadcastで伸ばされる軸のstrideは0になるため、データはコピーされない。
+            #[inline]
+            pub fn broadcast_to(
+                &self,
+                shape: Shape,
+            ) -> Result<CpuViewTensor<$lt, E>, BroadcastError> {
+                cpu_broadcast_to(self.to_view(), shape)
+            }
+
+            /// axesの順番に軸を並べ替える。shapeとstrideを入れ替えるだけでデータはコピーされない。
+            /// エラーの場合はviewを返さずに破棄する。
+            #[inline]
+            pub fn try_permute(self, axes: &[usize]) -> Result<Self, TensorError> {
+                let (shape, stride) =
+                    try_permute_update_shape_stride(&self.shape, &self.stride, axes)?;
+                Ok(TensorBase {
+                    ptr: self.ptr,
+                    shape,
+                    stride,
+                    num_elm: self.num_elm,
+                })
+            }
+
+            #[inline]
+            pub fn permute(self, axes: &[usize]) -> Self {
+                self.try_permute(axes).unwrap_or_else(|e| panic!("{}", e))
+            }
+
+            /// 最後の2つの軸を入れ替える。1次元の場合はそのまま返す。
+            #[inline]
+            pub fn t(self) -> Self {
+                let axes = transpose_axes(self.shape.num_dim());
+                self.permute(&axes)
+            }
+
+            /// `t`と同じ
+            #[inline]
+            pub fn transpose(self) -> Self {
+                self.t()
+            }
+
+            #[inline]
+            fn with_layout(self, shape: Shape, stride: Stride) -> Self {
+                TensorBase {
+                    ptr: self.ptr,
+                    shape,
+                    stride,
+                    num_elm: self.num_elm,
+                }
+            }
+
+            /// 大きさ1の軸axisを取り除く。
+            #[inline]
+            pub fn try_squeeze(self, axis: usize) -> Result<Self, TensorError> {
+                let (shape, stride) = squeeze_update_shape_stride(&self.shape, &self.stride, axis)?;
+                Ok(self.with_layout(shape, stride))
+            }
+
+            #[inline]
+            pub fn squeeze(self, axis: usize) -> Self {
+                self.try_squeeze(axis).unwrap_or_else(|e| panic!("{}", e))
+            }
+
+            /// axisの位置に大きさ1の軸を挿入する。axisは0からnum_dimまで指定できる。
+            #[inline]
+            pub fn try_unsqueeze(self, axis: usize) -> Result<Self, TensorError> {
+                let (shape, stride) =
+                    unsqueeze_update_shape_stride(&self.shape, &self.stride, axis)?;
+                Ok(self.with_layout(shape, stride))
+            }
+
+            #[inline]
+            pub fn unsqueeze(self, axis: usize) -> Self {
+                self.try_unsqueeze(axis).unwrap_or_else(|e| panic!("{}", e))
+            }
+
+            /// `start`から`end`まで(`end`を含む)の軸を1つにまとめる。
+            /// strideで表せない場合はエラーになるので、コピーしてもよい場合は`reshape`を使うこと。
+            #[inline]
+            pub fn try_flatten(self, start: usize, end: usize) -> Result<Self, TensorError> {
+                let (shape, stride) =
+                    flatten_update_shape_stride(&self.shape, &self.stride, start, end)?;
+                Ok(self.with_layout(shape, stride))
+            }
+
+            #[inline]
+            pub fn flatten(self, start: usize, end: usize) -> Self {
+                self.try_flatten(start, end)
+                    .unwrap_or_else(|e| panic!("{}", e))
+            }
+
+            /// 軸axisを`sizes`の大きさの軸に分ける。
+            #[inline]
+            pub fn try_unflatten(self, axis: usize, sizes: &[isize]) -> Result<Self, TensorError> {
+                let (shape, stride) =
+                    unflatten_update_shape_stride(&self.shape, &self.stride, axis, sizes)?;
+                Ok(self.with_layout(shape, stride))
+            }
+
+            #[inline]
+            pub fn unflatten(self, axis: usize, sizes: &[isize]) -> Self {
+                self.try_unflatten(axis, sizes)
+                    .unwrap_or_else(|e| panic!("{}", e))
+            }
+
+            /// 大きさ1の軸をshapeの大きさに伸ばしたviewを返す。shapeの-1は元の大きさのままにする。
+            #[inline]
+            pub fn try_expand(&self, shape: Shape) -> Result<CpuViewTensor<$lt, E>, TensorError> {
+                let (shape, stride) =
+                    expand_update_shape_stride(&self.shape, &self.stride, &shape)?;
+                let mut view = self.to_view();
+                view.shape = shape;
+                view.stride = stride;
+                Ok(view)
+            }
+
+            #[inline]
+            pub fn expand(&self, shape: Shape) -> CpuViewTensor<$lt, E> {
+                self.try_expand(shape).unwrap_or_else(|e| panic!("{}", e))
+            }
+
+            /// strideがdefaultの場合はviewを、そうでない場合はdefaultのstrideでコピーしたtensorを返す。
+            #[inline]
+            pub fn contiguous(&self) -> CpuCowTensor<$lt, E> {
+                cpu_contiguous(self.to_view())
+            }
+
+            /// shapeを変更する。今のstrideで表せる場合はviewを、表せない場合はコピーしたtensorを返す。
+            /// どちらになったかは`CpuCowTensor::is_view`で確認できる。
+            #[inline]
+            pub fn try_reshape(&self, shape: Shape) -> Result<CpuCowTensor<$lt, E>, TensorError> {
+                cpu_reshape(self.to_view(), shape)
+            }
+
+            #[inline]
+            pub fn reshape(&self, shape: Shape) -> CpuCowTensor<$lt, E> {
+                self.try_reshape(shape).unwrap_or_else(|e| panic!("{}", e))
+            }
+        }
+    };
+}
+
+// `CpuViewTensor`から作ったviewは元のviewと同じ間だけ、
+// `CpuViewMutTensor`から作ったviewは`&self`を借用している間だけ使える
+impl_view_transform!(CpuViewTensor, 'a);
+impl_view_transform!(CpuViewMutTensor, '_);
+
+impl<'a, E: Copy> CpuViewMutTensor<'a, E> {
+    /// 同じメモリを可変で参照するviewを返す。返したviewを使っている間は`self`を使えない。
+    #[inline]
+    pub fn to_view_mut(&mut self) -> CpuViewMutTensor<'_, E> {
+        TensorBase {
+            ptr: self.ptr.reborrow(),
+            shape: self.shape.clone(),
+            stride: self.stride.clone(),
+            num_elm: self.num_elm,
+        }
+    }
+
     #[inline]
-    pub fn to_slice_mut(&'_ self) -> &'_ mut [E] {
+    pub fn to_slice_mut(&'_ mut self) -> &'_ mut [E] {
         let mut sorted_stride = self.stride.to_vec();
         sorted_stride.sort();
         sorted_stride.reverse();
         self.ptr.to_slice_mut()
     }
 }
+
+impl<'a, E: Copy> CpuCowTensor<'a, E> {
+    #[inline]
+    pub fn is_view(&self) -> bool {
+        matches!(self, CpuCowTensor::View(_))
+    }
+
+    #[inline]
+    pub fn shape(&self) -> Shape {
+        match self {
+            CpuCowTensor::View(a) => a.shape(),
+            CpuCowTensor::Owned(a) => a.shape(),
+        }
+    }
+
+    /// 保持しているtensorを参照するviewを返す。
+    #[inline]
+    pub fn to_view(&self) -> CpuViewTensor<'_, E> {
+        match self {
+            CpuCowTensor::View(a) => a.to_view(),
+            CpuCowTensor::Owned(a) => a.to_view(),
+        }
+    }
+
+    /// `View`の場合は参照している要素をコピーし、`Owned`の場合はそのまま返す。
+    #[inline]
+    pub fn into_owned(self) -> CpuTensor<E> {
+        match self {
+            CpuCowTensor::View(a) => TensorBase::from_vec(a.iter().copied().collect(), a.shape()),
+            CpuCowTensor::Owned(a) => a,
+        }
+    }
+}
+
+#[test]
+fn view_mut_reborrow_test() {
+    let mut a = CpuTensor::from_vec(vec![0, 1, 2, 3], Shape::new(vec![2, 2]));
+    let mut v = a.to_view_mut();
+    v.to_view_mut().to_slice_mut()[1] = 10;
+    {
+        let r = v.to_view();
+        assert_eq!(r.iter().copied().collect::<Vec<_>>(), vec![0, 10, 2, 3]);
+    }
+    v.to_slice_mut()[2] = 20;
+    assert_eq!(a.to_vec(), vec![0, 10, 20, 3]);
+}
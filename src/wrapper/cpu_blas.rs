@@ -5,18 +5,39 @@ use num_traits::Num;
 use crate::define_impl;
 use cblas::*;
 
+// cblasの複素数の内積は結果を引数で受け取るため、戻り値で返す関数にしておく
+macro_rules! dot_sub {
+    ($name:ident, $sub:ident, $ty:ty) => {
+        unsafe fn $name(n: i32, x: &[$ty], incx: i32, y: &[$ty], incy: i32) -> $ty {
+            let mut out = [<$ty>::new(0., 0.)];
+            $sub(n, x, incx, y, incy, &mut out);
+            out[0]
+        }
+    };
+}
+
+dot_sub!(cdotu, cdotu_sub, c32);
+dot_sub!(zdotu, zdotu_sub, c64);
+dot_sub!(cdotc, cdotc_sub, c32);
+dot_sub!(zdotc, zdotc_sub, c64);
+
 // Lebel 1
 define_impl!(
     CpuAsum,
     cpu_asum,
-    ((sasum, f32, f32), (dasum, f64, f64)),
+    (
+        (sasum, f32, f32),
+        (dasum, f64, f64),
+        (scasum, c32, f32),
+        (dzasum, c64, f64)
+    ),
     (n: i32, x: &[Self], incx: i32)
 );
 
 define_impl!(
     CpuAxpy,
     cpu_axpy,
-    ((saxpy, f32), (daxpy, f64)),
+    ((saxpy, f32), (daxpy, f64), (caxpy, c32), (zaxpy, c64)),
     (
         n: i32,
         alpha: Self,
@@ -41,6 +62,32 @@ define_impl!(
     (n: i32, x: &[Self], incx: i32, y: &[Self], incy: i32)
 );
 
+// 複素数では共役を取らない内積。実数では`dot`と同じ。
+define_impl!(
+    CpuDotu,
+    cpu_dotu,
+    (
+        (sdot, f32, f32),
+        (ddot, f64, f64),
+        (cdotu, c32, c32),
+        (zdotu, c64, c64)
+    ),
+    (n: i32, x: &[Self], incx: i32, y: &[Self], incy: i32)
+);
+
+// 複素数ではxの共役を取る内積。実数では`dot`と同じ。
+define_impl!(
+    CpuDotc,
+    cpu_dotc,
+    (
+        (sdot, f32, f32),
+        (ddot, f64, f64),
+        (cdotc, c32, c32),
+        (zdotc, c64, c64)
+    ),
+    (n: i32, x: &[Self], incx: i32, y: &[Self], incy: i32)
+);
+
 define_impl!(
     CpuSdot,
     cpu_sdot,
@@ -51,7 +98,12 @@ define_impl!(
 define_impl!(
     CpuNrm2,
     cpu_nrm2,
-    ((snrm2, f32, f32), (dnrm2, f64, f64)),
+    (
+        (snrm2, f32, f32),
+        (dnrm2, f64, f64),
+        (scnrm2, c32, f32),
+        (dznrm2, c64, f64)
+    ),
     (n: i32, x: &[Self], incx: i32)
 );
 
@@ -152,7 +204,7 @@ define_impl!(
 define_impl!(
     CpuGemv,
     cpu_gemv,
-    ((sgemv, f32), (dgemv, f64)),
+    ((sgemv, f32), (dgemv, f64), (cgemv, c32), (zgemv, c64)),
     (
         layout: Layout,
         transa: Transpose,
@@ -190,7 +242,25 @@ define_impl!(
 define_impl!(
     CpuGerc,
     cpu_gerc,
-    ((sger, f32), (dger, f64)),
+    ((sger, f32), (dger, f64), (cgerc, c32), (zgerc, c64)),
+    (
+        layout: Layout,
+        m: i32,
+        n: i32,
+        alpha: Self,
+        x: &[Self],
+        incx: i32,
+        y: &[Self],
+        incy: i32,
+        a: &mut [Self],
+        lda: i32
+    )
+);
+
+define_impl!(
+    CpuGeru,
+    cpu_geru,
+    ((sger, f32), (dger, f64), (cgeru, c32), (zgeru, c64)),
     (
         layout: Layout,
         m: i32,
@@ -433,7 +503,7 @@ define_impl!(
 define_impl! {
     CpuGemm,
     cpu_gemm,
-    ((sgemm, f32), (dgemm, f64)),
+    ((sgemm, f32), (dgemm, f64), (cgemm, c32), (zgemm, c64)),
     (
         layout: Layout,
         transa: Transpose,
@@ -473,6 +543,74 @@ define_impl!(
     )
 );
 
+define_impl!(
+    CpuHemm,
+    cpu_hemm,
+    ((chemm, c32), (zhemm, c64)),
+    (
+        layout: Layout,
+        side: Side,
+        uplo: Part,
+        m: i32,
+        n: i32,
+        alpha: Self,
+        a: &[Self],
+        lda: i32,
+        b: &[Self],
+        ldb: i32,
+        beta: Self,
+        c: &mut [Self],
+        ldc: i32
+    )
+);
+
+/// herkのalpha, betaは実数なので、`define_impl!`を使わずに定義する
+pub trait CpuHerk: Sized + Num + Copy + Debug {
+    type Real: Sized + Copy;
+    #[allow(clippy::too_many_arguments)]
+    fn cpu_herk(
+        layout: Layout,
+        uplo: Part,
+        trans: Transpose,
+        n: i32,
+        k: i32,
+        alpha: Self::Real,
+        a: &[Self],
+        lda: i32,
+        beta: Self::Real,
+        c: &mut [Self],
+        ldc: i32,
+    );
+}
+
+macro_rules! impl_herk {
+    ($(($call_fn:ident, $impl_ty:ty, $real:ty)),*) => {
+        $(
+            impl CpuHerk for $impl_ty {
+                type Real = $real;
+                #[inline(always)]
+                fn cpu_herk(
+                    layout: Layout,
+                    uplo: Part,
+                    trans: Transpose,
+                    n: i32,
+                    k: i32,
+                    alpha: $real,
+                    a: &[Self],
+                    lda: i32,
+                    beta: $real,
+                    c: &mut [Self],
+                    ldc: i32,
+                ) {
+                    unsafe { $call_fn(layout, uplo, trans, n, k, alpha, a, lda, beta, c, ldc) }
+                }
+            }
+        )*
+    };
+}
+
+impl_herk!((cherk, c32, f32), (zherk, c64, f64));
+
 define_impl!(
     CpuSyrk,
     cpu_syrk,
@@ -0,0 +1,40 @@
+//! キャッシュの有効・無効はプロセス全体の設定なので、他のtestと並列に動かないように別のbinaryにする。
+use bokutotu::memory_pool::{empty_cache, memory_stats, set_caching_enabled};
+use bokutotu::shape::Shape;
+use bokutotu::tensor::CpuTensor;
+
+#[test]
+fn memory_pool_test() {
+    const BYTES: usize = 1 << 20;
+    set_caching_enabled(true);
+    let a = CpuTensor::<f64>::zeros(Shape::new(vec![(BYTES / 8) as isize]));
+    let ptr = a.as_ptr() as usize;
+    let stats = memory_stats();
+    assert_eq!(stats.bytes_in_use, BYTES);
+    assert_eq!(stats.peak_bytes_in_use, BYTES);
+    drop(a);
+    let stats = memory_stats();
+    assert_eq!(stats.bytes_in_use, 0);
+    assert_eq!(stats.bytes_cached, BYTES);
+
+    // 同じbyte数とalignであれば、別の型でも同じメモリを使う
+    let b = CpuTensor::<i64>::ones(Shape::new(vec![(BYTES / 8) as isize]));
+    assert_eq!(b.as_ptr() as usize, ptr);
+    assert_eq!(memory_stats().bytes_cached, 0);
+    assert!(b.iter().all(|x| *x == 1));
+    let c = &b + &b;
+    assert_eq!(c.to_vec()[..2], [2, 2]);
+    assert_eq!(memory_stats().peak_bytes_in_use, 2 * BYTES);
+    drop(b);
+    drop(c);
+    assert_eq!(memory_stats().bytes_cached, 2 * BYTES);
+    empty_cache();
+    assert_eq!(memory_stats().bytes_cached, 0);
+
+    // bucketのサイズでないVecはキャッシュに残さずに解放する
+    drop(CpuTensor::from_vec(vec![1u8; 3], Shape::new(vec![3])));
+    let stats = memory_stats();
+    assert_eq!(stats.bytes_in_use, 0);
+    assert_eq!(stats.bytes_cached, 0);
+    set_caching_enabled(false);
+}